@@ -0,0 +1,36 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+
+#[test]
+fn archive_flow() {
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let repo = Repository::new("/tmp/r".into());
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    repo.add_branch(Branch::new("old".into(), a.clone(), 1));
+    repo.add_branch(Branch::new("fresh".into(), a.clone(), 1));
+    repo.checkout("fresh", "bob", 2);
+    repo.commit(mk(&[], 50, "x", &[("f", "2\n")])).unwrap();
+    repo.checkout("main", "bob", 51);
+    let r = repo.sweep(10, "bob", 60);
+    assert_eq!(r.get_archived(), &vec![("old".to_string(), 1)]);
+    assert_eq!(r.get_kept().len(), 2);
+    {
+        let ar = repo.get_archived();
+        let ar = ar.lock().unwrap();
+        assert_eq!(ar[0].get_ref(), "refs/archive/old");
+        assert!(matches!(
+            ar[0].add_commit(a.clone()),
+            Err(branch::Rejection::Archived(_))
+        ));
+    }
+    repo.undo("bob", 61).unwrap().unwrap();
+    assert_eq!(repo.get_archived().lock().unwrap().len(), 0);
+    repo.redo("bob", 61).unwrap().unwrap();
+    repo.unarchive("old", "bob", 70);
+    let br = repo.get_branches();
+    let br = br.lock().unwrap();
+    let old = br.iter().find(|b| b.get_name() == "old").unwrap();
+    assert_eq!(old.get_last_active(), 70);
+    assert!(!old.is_archived());
+}
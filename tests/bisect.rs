@@ -0,0 +1,83 @@
+use bisect::*;
+use gitlike::repository::*;
+
+fn chain(n: usize) -> graph::Graph {
+    let mut g = graph::Graph::new();
+    for i in 0..n {
+        let mut c = Commit::new(format!("c{i:02}"), "a".into(), "m".into(), i as u64);
+        if i > 0 {
+            c.add_parent(format!("c{:02}", i - 1));
+        }
+        g.add_commit(c);
+    }
+    g
+}
+
+#[test]
+fn bisect_finds() {
+    let g = chain(40);
+    let mut s = Session::new("c00".into(), "c39".into());
+    let out = s.run(&g, |c| {
+        if c.get_hash().as_str() >= "c17" {
+            Verdict::Bad
+        } else {
+            Verdict::Good
+        }
+    });
+    assert_eq!(out, Ok(Outcome::Found("c17".into())));
+    assert!(s.get_log().len() < 10);
+    let p = std::env::temp_dir().join(format!("bisect-state-{}", std::process::id()));
+    s.save(&p).unwrap();
+    let l = Session::load(&p).unwrap();
+    assert_eq!(l.next(&g), Ok(Outcome::Found("c17".into())));
+    let mut s = Session::new("c00".into(), "c39".into());
+    let out = s.run_command(&g, "test \"$BISECT_COMMIT\" \\< c25");
+    assert_eq!(out, Ok(Outcome::Found("c25".into())));
+    let mut s = Session::new("c00".into(), "c39".into());
+    let out = s.run(&g, |c| {
+        if c.get_hash() == "c16" {
+            Verdict::Skip
+        } else if c.get_hash().as_str() >= "c17" {
+            Verdict::Bad
+        } else {
+            Verdict::Good
+        }
+    });
+    assert_eq!(
+        out,
+        Ok(Outcome::Inconclusive(vec!["c16".into(), "c17".into()]))
+    );
+}
+
+#[test]
+fn bisect_skipped_bad() {
+    let g = chain(2);
+    let mut s = Session::new("c00".into(), "c01".into());
+    s.mark("c01".into(), Verdict::Skip);
+    assert_eq!(s.next(&g), Ok(Outcome::Inconclusive(vec!["c01".into()])));
+}
+
+#[test]
+fn bisect_missing() {
+    let g = chain(3);
+    let s = Session::new("c00".into(), "gone".into());
+    assert_eq!(s.next(&g), Err(Error::MissingCommit("gone".into())));
+    let mut s = Session::new("lost".into(), "c02".into());
+    assert_eq!(
+        s.run(&g, |_| Verdict::Good),
+        Err(Error::MissingCommit("lost".into()))
+    );
+}
+
+#[test]
+fn bisect_stops_on_signals() {
+    let g = chain(8);
+    let mut s = Session::new("c00".into(), "c07".into());
+    let out = s.run_command(&g, "exit 129");
+    assert!(matches!(out, Err(Error::Aborted(_))));
+    assert_eq!(s.get_log().len(), 2);
+    let mut s = Session::new("c00".into(), "c07".into());
+    let out = s.run_command(&g, "kill -9 $$");
+    assert!(matches!(out, Err(Error::Aborted(_))));
+    assert_eq!(s.get_log().len(), 2);
+}
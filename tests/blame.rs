@@ -0,0 +1,73 @@
+use gitlike::repository::*;
+use std::collections::BTreeMap;
+
+fn commit(hash: &str, parents: &[&str], ts: u64, files: &[(&str, &str)]) -> Commit {
+    let mut c = Commit::new(hash.into(), format!("a-{hash}"), "m".into(), ts);
+    for p in parents {
+        c.add_parent(p.to_string());
+    }
+    let snap: BTreeMap<String, Vec<u8>> = files
+        .iter()
+        .map(|(p, c)| (p.to_string(), c.as_bytes().to_vec()))
+        .collect();
+    c.set_tree(Folder::from_snapshot(&snap, "x", ts));
+    c
+}
+
+#[test]
+fn blame_basic() {
+    let mut g = graph::Graph::new();
+    g.add_commit(commit("a", &[], 1, &[("src/f.txt", "one\ntwo\nthree\n")]));
+    g.add_commit(commit(
+        "b",
+        &["a"],
+        2,
+        &[("src/f.txt", "one\nTWO\nthree\nfour\n")],
+    ));
+    g.add_commit(commit(
+        "c",
+        &["b"],
+        3,
+        &[("src/g.txt", "one\nTWO\nthree\nfour\n"), ("x", "y\n")],
+    ));
+    g.add_commit(commit(
+        "d",
+        &["c"],
+        4,
+        &[("src/g.txt", "  one\nTWO\nthree\nfour\n"), ("x", "y\n")],
+    ));
+    let o = blame::Options::new(false, true, vec![]);
+    let r = blame::annotate(&g, "d", "src/g.txt", &o).unwrap();
+    let h: Vec<_> = r.iter().map(|l| l.get_hash().clone()).collect();
+    assert_eq!(h, vec!["d", "b", "a", "b"]);
+    let o = blame::Options::new(true, true, vec![]);
+    let r = blame::annotate(&g, "d", "src/g.txt", &o).unwrap();
+    let h: Vec<_> = r.iter().map(|l| l.get_hash().clone()).collect();
+    assert_eq!(h, vec!["a", "b", "a", "b"]);
+    assert_eq!(r[0].get_path(), "src/f.txt");
+    let o = blame::Options::new(false, true, vec!["d".into()]);
+    let r = blame::annotate(&g, "d", "src/g.txt", &o).unwrap();
+    let h: Vec<_> = r.iter().map(|l| l.get_hash().clone()).collect();
+    assert_eq!(h, vec!["a", "b", "a", "b"]);
+    let o = blame::Options::new(false, false, vec![]);
+    let r = blame::annotate(&g, "d", "src/g.txt", &o).unwrap();
+    let h: Vec<_> = r.iter().map(|l| l.get_hash().clone()).collect();
+    assert_eq!(h, vec!["d", "c", "c", "c"]);
+    assert_eq!(g.walk("d"), vec!["d", "c", "b", "a"]);
+    assert_eq!(g.range("b", "d"), vec!["c", "d"]);
+}
+
+#[test]
+fn diff_random() {
+    let a: Vec<i32> = vec![1, 2, 3, 4, 5, 6, 7];
+    let b: Vec<i32> = vec![0, 2, 3, 9, 5, 7, 8];
+    let ops = diff::compute(&a, &b);
+    let eq = ops
+        .iter()
+        .filter(|o| matches!(o, diff::Op::Equal(..)))
+        .count();
+    assert_eq!(eq, 4);
+    let empty: Vec<i32> = vec![];
+    assert_eq!(diff::compute(&empty, &b).len(), 7);
+    assert_eq!(diff::compute(&a, &empty).len(), 7);
+}
@@ -0,0 +1,82 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+
+#[test]
+fn branch_ops() {
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let repo = Repository::new("/tmp/r".into());
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    repo.add_branch(Branch::new("topic".into(), a.clone(), 1));
+    repo.checkout("topic", "bob", 2);
+    let b = repo.commit(mk(&[], 3, "t", &[("f", "2\n")])).unwrap();
+    assert_eq!(
+        repo.delete_branch("topic", false, "bob", 4).err(),
+        Some(branch::Rejection::Current("topic".into()))
+    );
+    repo.checkout("main", "bob", 4);
+    assert_eq!(
+        repo.delete_branch("topic", false, "bob", 4).err(),
+        Some(branch::Rejection::Unmerged("topic".into()))
+    );
+    repo.set_upstream("main", None);
+    repo.rename_branch("topic", "feature", "bob", 5).unwrap();
+    {
+        let br = repo.get_branches();
+        let br = br.lock().unwrap();
+        let f = br.iter().find(|b| b.get_name() == "feature").unwrap();
+        assert_eq!(f.get_reflog().lock().unwrap().get_entries().len(), 3);
+        assert_eq!(
+            f.get_reflog().lock().unwrap().get_name(),
+            "refs/heads/feature"
+        );
+    }
+    let ctx = protection::Context::new("bob".into());
+    assert_eq!(
+        repo.update_ref("main", "nope", b.get_hash(), &ctx, 6).err(),
+        Some(branch::Rejection::Stale(
+            "nope".into(),
+            a.get_hash().clone()
+        ))
+    );
+    repo.update_ref("main", a.get_hash(), b.get_hash(), &ctx, 6)
+        .unwrap();
+    assert_eq!(repo.resolve("main").unwrap(), *b.get_hash());
+    assert_eq!(
+        repo.update_ref("main", b.get_hash(), a.get_hash(), &ctx, 7)
+            .err(),
+        Some(branch::Rejection::NonFastForward("main".into()))
+    );
+    repo.delete_branch("feature", false, "bob", 8).unwrap();
+    assert_eq!(repo.get_branches().lock().unwrap().len(), 1);
+}
+
+#[test]
+fn delete_and_rename_guards() {
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let repo = Repository::new("/tmp/r".into());
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    repo.add_branch(Branch::new("topic".into(), a.clone(), 1));
+    repo.add_branch(Branch::new("other".into(), a.clone(), 1));
+    assert_eq!(
+        repo.delete_branch("gone", false, "bob", 2).err(),
+        Some(branch::Rejection::Missing("gone".into()))
+    );
+    assert_eq!(
+        repo.rename_branch("topic", "other", "bob", 2).err(),
+        Some(branch::Rejection::Exists("other".into()))
+    );
+    repo.checkout("topic", "bob", 3);
+    repo.commit(mk(&[], 4, "t", &[("f", "2\n")])).unwrap();
+    repo.checkout("main", "bob", 5);
+    repo.protect(protection::Rule::new("other".into()));
+    assert_eq!(
+        repo.delete_branch("other", true, "bob", 6).err(),
+        Some(branch::Rejection::Rewrite("other".into()))
+    );
+    let removed = repo.delete_branch("topic", true, "bob", 6).unwrap();
+    assert_eq!(removed.get_name(), "topic");
+    assert!(repo.resolve("topic").is_none());
+    repo.undo("bob", 7).unwrap().unwrap();
+    assert!(repo.resolve("topic").is_some());
+}
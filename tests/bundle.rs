@@ -0,0 +1,110 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+
+#[test]
+fn bundles() {
+    let dir = std::env::temp_dir().join(format!("bundle-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let src = Repository::new("/tmp/src".into());
+    src.add_branch(Branch::new("main".into(), a.clone(), 1));
+    let b = src.commit(mk(&[], 2, "b", &[("f", "2\n")])).unwrap();
+    src.add_tag(Tag::new("v1".into(), &b, "rel".into()), "alice", 2);
+    let full = bundle::Bundle::create(&src, &[], &[]).unwrap();
+    assert!(full.get_prerequisites().is_empty());
+    let thin = bundle::Bundle::create(&src, &["main", "v1"], &[a.get_hash()]).unwrap();
+    assert_eq!(thin.get_prerequisites(), &vec![a.get_hash().clone()]);
+    let path = dir.join("thin.bundle");
+    thin.write(&path).unwrap();
+    assert_eq!(
+        bundle::Bundle::read(&path).unwrap().get_refs(),
+        thin.get_refs()
+    );
+
+    let empty = Repository::new("/tmp/e".into());
+    empty.add_remote(Remote::new(
+        "usb".into(),
+        path.to_string_lossy().to_string(),
+    ));
+    assert!(matches!(
+        transport::fetch(&empty, "usb", 3),
+        Err(transport::Error::Bundle(bundle::Error::Prerequisites(_)))
+    ));
+
+    let local = Repository::new("/tmp/l".into());
+    local.add_branch(Branch::new("main".into(), a.clone(), 1));
+    local.add_remote(Remote::new(
+        "usb".into(),
+        path.to_string_lossy().to_string(),
+    ));
+    let r = transport::fetch(&local, "usb", 3).unwrap();
+    assert!(r.is_ok());
+    assert_eq!(r.get_updates().len(), 2);
+    assert_eq!(local.get_tags().lock().unwrap()[0].get_message(), "rel");
+    assert!(transport::push(&local, "usb", &[], true, 4).is_err());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn shallow_history_becomes_prerequisites() {
+    let dir = std::env::temp_dir().join(format!("bundle-shallow-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let up = dir.join("up").to_string_lossy().to_string();
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let origin = Repository::new(up.clone());
+    origin.add_branch(Branch::new("main".into(), a.clone(), 1));
+    let b = origin.commit(mk(&[], 2, "b", &[("f", "2\n")])).unwrap();
+    let c = origin.commit(mk(&[], 3, "c", &[("f", "3\n")])).unwrap();
+    origin.save().unwrap();
+
+    let local = Repository::new(dir.join("down").to_string_lossy().to_string());
+    let mut remote = Remote::new("origin".into(), up);
+    remote.set_fetch(&["+refs/heads/*:refs/heads/*"]).unwrap();
+    local.add_remote(remote);
+    let mut options = transport::Options::new();
+    options.set_depth(Some(2));
+    transport::fetch_with(&local, "origin", &options, 2).unwrap();
+    assert!(local.get_shallow().lock().unwrap().contains(b.get_hash()));
+    let thin = bundle::Bundle::create(&local, &["main"], &[]).unwrap();
+    assert_eq!(thin.get_prerequisites(), &vec![a.get_hash().clone()]);
+    assert_eq!(thin.get_pack().commits().unwrap().len(), 2);
+    assert!(thin.encode().starts_with(b"# gitlike bundle v1\n"));
+
+    let target = store::Store::new(&dir.join("target"));
+    target.init().unwrap();
+    assert!(matches!(
+        thin.unbundle(&target),
+        Err(bundle::Error::Prerequisites(missing)) if missing == vec![a.get_hash().clone()]
+    ));
+    assert!(!target.has(c.get_hash()));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn unbundle_rejects_tampered_objects() {
+    let dir = std::env::temp_dir().join(format!("bundle-tamper-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let a = mk(&[], 1, "init", &[("f", "original\n")]);
+    let src = Repository::new("/tmp/src".into());
+    src.add_branch(Branch::new("main".into(), a.clone(), 1));
+    let full = bundle::Bundle::create(&src, &[], &[]).unwrap();
+    let target = store::Store::new(&dir.join("target"));
+    target.init().unwrap();
+    let data = full.encode();
+    let needle = b"original\n";
+    let at = data
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .unwrap();
+    let mut forged = data.clone();
+    assert!(forged.starts_with(bundle::SIGNATURE.as_bytes()));
+    forged[at..at + needle.len()].copy_from_slice(b"tampered\n");
+    let forged = bundle::Bundle::decode(&forged).unwrap();
+    assert!(forged.unbundle(&target).is_err());
+    assert!(!target.has(a.get_hash()));
+    assert_eq!(full.unbundle(&target).unwrap(), 3);
+    assert!(target.has(a.get_hash()));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,24 @@
+#![allow(dead_code)]
+use gitlike::repository::*;
+use std::collections::BTreeMap;
+
+pub fn snap(files: &[(&str, &str)]) -> BTreeMap<String, Vec<u8>> {
+    files
+        .iter()
+        .map(|(p, c)| (p.to_string(), c.as_bytes().to_vec()))
+        .collect()
+}
+
+pub fn mk(parents: &[&str], ts: u64, msg: &str, files: &[(&str, &str)]) -> Commit {
+    Commit::from_snapshot(
+        &snap(files),
+        parents.iter().map(|s| s.to_string()).collect(),
+        "alice".into(),
+        msg.into(),
+        ts,
+    )
+}
+
+pub fn text(c: &Commit, p: &str) -> String {
+    String::from_utf8(c.get_snapshot()[p].clone()).unwrap()
+}
@@ -0,0 +1,179 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn sample() -> (Repository, Commit, Commit) {
+    let repo = Repository::new("mem".into());
+    let c1 = mk(
+        &[],
+        100,
+        "first\n",
+        &[("a", "1"), ("dir/b c", "2"), ("q\"uote", "3")],
+    );
+    let h1 = c1.get_hash().clone();
+    repo.add_branch(Branch::new("main".into(), c1.clone(), 100));
+    let c2 = repo
+        .commit(mk(
+            &[&h1],
+            200,
+            "second\n",
+            &[("a", "11"), ("dir/b c", "2"), ("new", "n")],
+        ))
+        .unwrap();
+    repo.add_branch(Branch::new(
+        "side".into(),
+        mk(&[&h1], 150, "side\n", &[("a", "1")]),
+        150,
+    ));
+    repo.add_tag(Tag::new("v1".into(), &c1, "release\n".into()), "alice", 300);
+    (repo, c1, c2)
+}
+
+#[test]
+fn roundtrip() {
+    let (repo, _c1, c2) = sample();
+    let stream = fast::export(&repo).unwrap();
+    let text = String::from_utf8_lossy(&stream);
+    assert!(text.contains("M 100644 :"), "{}", text);
+    assert!(text.contains("D \"q\\\"uote\""), "{}", text);
+    let copy = Repository::new("copy".into());
+    let report = fast::import(&copy, &stream, false, 500).unwrap();
+    assert!(report.is_ok());
+    assert_eq!(report.get_updates().len(), 3);
+    let head = {
+        let b = copy.get_branches();
+        let b = b.lock().unwrap();
+        let main = b.iter().find(|x| x.get_name() == "main").unwrap();
+        let head = main.get_head().lock().unwrap().clone();
+        head
+    };
+    assert_eq!(head.get_hash(), c2.get_hash());
+    let kinds: Vec<String> = head
+        .get_changes()
+        .iter()
+        .map(|c| format!("{:?} {}", c.get_change_type(), c.get_file_path()))
+        .collect();
+    assert_eq!(kinds, vec!["Delete q\"uote", "Modify a", "Add new"]);
+    assert_eq!(
+        copy.get_tags().lock().unwrap()[0].get_message(),
+        "release\n"
+    );
+    assert_eq!(copy.get_head(), "main");
+    let again = fast::import(&copy, &stream, false, 600).unwrap();
+    assert!(again.is_ok() && again.get_updates().is_empty());
+}
+
+#[test]
+fn with_git() {
+    let (repo, _, c2) = sample();
+    let dir = std::env::temp_dir().join(format!("fast-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    Command::new("git")
+        .args(["init", "-q", "--bare"])
+        .arg(&dir)
+        .status()
+        .unwrap();
+    let mut child = Command::new("git")
+        .arg("--git-dir")
+        .arg(&dir)
+        .args(["fast-import", "--quiet"])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(&fast::export(&repo).unwrap())
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let show = Command::new("git")
+        .arg("--git-dir")
+        .arg(&dir)
+        .args(["show", "main:dir/b c"])
+        .output()
+        .unwrap();
+    assert_eq!(show.stdout, b"2");
+    let out = Command::new("git")
+        .arg("--git-dir")
+        .arg(&dir)
+        .args(["fast-export", "--all", "-M", "-C"])
+        .output()
+        .unwrap();
+    let copy = Repository::new("copy".into());
+    let report = fast::import(&copy, &out.stdout, false, 1).unwrap();
+    assert!(report.is_ok(), "{:?}", report.get_rejected());
+    let b = copy.get_branches();
+    let b = b.lock().unwrap();
+    let main = b.iter().find(|x| x.get_name() == "main").unwrap();
+    assert_eq!(
+        main.get_head().lock().unwrap().get_snapshot(),
+        c2.get_snapshot()
+    );
+    assert_eq!(main.get_head().lock().unwrap().get_author(), "alice");
+    let stream = b"blob\nmark :1\ndata <<EOF\nhi\nEOF\ncommit refs/heads/x\ncommitter Bob <b@x> 5 +0000\ndata 2\nm\nM 100644 :1 f\nM 100644 inline g\ndata 1\nz\nC f \"h i\"\nR g k\n\ndone\n";
+    let r = Repository::new("r".into());
+    fast::import(&r, stream, false, 1).unwrap();
+    let snap = r.get_branches().lock().unwrap()[0]
+        .get_head()
+        .lock()
+        .unwrap()
+        .get_snapshot();
+    assert_eq!(
+        snap.keys().cloned().collect::<Vec<_>>(),
+        vec!["f", "h i", "k"]
+    );
+    assert_eq!(snap["h i"], b"hi\n");
+    assert!(matches!(
+        fast::import(&r, b"bogus\n", false, 1),
+        Err(fast::Error::Syntax(_))
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn refs_only_move_forward_unless_forced() {
+    let (repo, c1, _) = sample();
+    let stream = fast::export(&repo).unwrap();
+    let copy = Repository::new("copy".into());
+    copy.add_branch(Branch::new(
+        "main".into(),
+        mk(&[], 1, "other\n", &[("o", "o")]),
+        1,
+    ));
+    copy.add_branch(Branch::new("side".into(), c1.clone(), 100));
+    copy.archive("side", "bob", 2);
+    let report = fast::import(&copy, &stream, false, 3).unwrap();
+    let rejected: Vec<_> = report
+        .get_rejected()
+        .iter()
+        .map(|(r, why)| (r.as_str(), why.clone()))
+        .collect();
+    assert_eq!(
+        rejected,
+        vec![
+            (
+                "refs/heads/main",
+                branch::Rejection::NonFastForward("refs/heads/main".into())
+            ),
+            (
+                "refs/heads/side",
+                branch::Rejection::Archived("side".into())
+            ),
+        ]
+    );
+    let forced = fast::import(&copy, &stream, true, 4).unwrap();
+    assert_eq!(forced.get_rejected().len(), 1);
+    assert!(forced
+        .get_updates()
+        .iter()
+        .any(|u| u.get_reference() == "refs/heads/main" && u.is_forced()));
+}
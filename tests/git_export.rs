@@ -0,0 +1,145 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+use std::process::Command;
+
+fn git(dir: &std::path::Path, args: &[&str]) -> String {
+    let out = Command::new("git")
+        .arg("--git-dir")
+        .arg(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn export() {
+    let dir = std::env::temp_dir().join(format!("git-export-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let repo = Repository::new("mem".into());
+    let c1 = mk(
+        &[],
+        100,
+        "first\n",
+        &[("a", "1"), ("b/c", "2"), ("b-x", "3"), ("b/d/e", "4")],
+    );
+    let h1 = c1.get_hash().clone();
+    repo.add_branch(Branch::new("main".into(), c1.clone(), 100));
+    let c2 = repo
+        .commit(mk(
+            &[&h1],
+            200,
+            "second\n",
+            &[("a", "1"), ("b/c", "22"), ("b-x", "3")],
+        ))
+        .unwrap();
+    repo.add_tag(Tag::new("v1".into(), &c1, "release\n".into()), "alice", 300);
+    let mut old = Branch::new("old".into(), mk(&[], 50, "Old\n", &[("z", "z")]), 50);
+    old.archive_if_inactive(0);
+    repo.add_branch(Branch::new("topic".into(), c2.clone(), 200));
+    let ids = repo.save_git(&dir).unwrap();
+    assert_eq!(ids.len(), 2);
+    git(&dir, &["fsck", "--strict"]);
+    assert_eq!(git(&dir, &["rev-parse", "main"]).trim(), ids[c2.get_hash()]);
+    assert_eq!(git(&dir, &["cat-file", "-p", "main:b/c"]), "22");
+    assert_eq!(
+        git(&dir, &["log", "--format=%an|%ae|%at|%s", "main"]),
+        "alice||200|second\nalice||100|first\n"
+    );
+    assert_eq!(git(&dir, &["cat-file", "-t", "v1"]).trim(), "tag");
+    assert_eq!(git(&dir, &["rev-parse", "v1^{commit}"]).trim(), ids[&h1]);
+    assert_eq!(
+        git(&dir, &["symbolic-ref", "HEAD"]).trim(),
+        "refs/heads/main"
+    );
+
+    let back = Repository::open_git(&dir).unwrap();
+    let branches = back.get_branches();
+    let branches = branches.lock().unwrap();
+    let main = branches.iter().find(|b| b.get_name() == "main").unwrap();
+    let head = main.get_head();
+    let head = head.lock().unwrap();
+    assert_eq!(head.get_author(), "alice");
+    assert_eq!(head.get_snapshot(), c2.get_snapshot());
+    assert_eq!(head.get_hash(), &ids[c2.get_hash()]);
+    assert_eq!(
+        back.get_tags().lock().unwrap()[0].get_message(),
+        "release\n"
+    );
+
+    repo.delete_branch("topic", true, "alice", 400).unwrap();
+    repo.save_git(&dir).unwrap();
+    assert!(!git(&dir, &["for-each-ref"]).contains("topic"));
+    git(&dir, &["gc", "-q"]);
+    git(&dir, &["fsck", "--strict"]);
+    assert_eq!(
+        Repository::open_git(&dir)
+            .unwrap()
+            .get_branches()
+            .lock()
+            .unwrap()
+            .len(),
+        1
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn export_is_idempotent_and_orders_trees_like_git() {
+    let dir = std::env::temp_dir().join(format!("git-export-again-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let repo = Repository::new("mem".into());
+    let c1 = mk(&[], 100, "first\n", &[("x/y/z", "deep"), ("x.txt", "flat")]);
+    repo.add_branch(Branch::new("main".into(), c1.clone(), 100));
+    let first = repo.save_git(&dir).unwrap();
+    let second = repo.save_git(&dir).unwrap();
+    assert_eq!(first, second);
+    assert_eq!(git(&dir, &["ls-tree", "--name-only", "main"]), "x.txt\nx\n");
+    assert_eq!(git(&dir, &["cat-file", "-p", "main:x/y/z"]), "deep");
+    git(&dir, &["fsck", "--strict"]);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn export_keeps_timezones_and_signatures() {
+    let dir = std::env::temp_dir().join(format!("git-export-zone-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let repo = Repository::new("mem".into());
+    let mut c1 = mk(&[], 100, "first\n", &[("a", "1")]);
+    c1.set_timezone("+0530".into());
+    c1.sign(|_| "-----BEGIN SSH SIGNATURE-----\nabc\n-----END SSH SIGNATURE-----\n".into());
+    repo.add_branch(Branch::new("release.1".into(), c1.clone(), 100));
+    repo.add_tag(Tag::new("v1".into(), &c1, "release\n".into()), "alice", 200);
+    let ids = repo.save_git(&dir).unwrap();
+    git(&dir, &["fsck", "--strict"]);
+    let body = git(&dir, &["cat-file", "-p", "release.1"]);
+    assert!(body.contains("author alice <> 100 +0530\n"));
+    assert!(body.contains("gpgsig -----BEGIN SSH SIGNATURE-----\n abc\n -----END"));
+    assert!(git(&dir, &["cat-file", "-p", "v1"]).contains("tagger alice <> 100 +0530\n"));
+    assert!(!dir.join("refs/heads/release.1.lock").exists());
+
+    let back = Repository::open_git(&dir).unwrap();
+    let branches = back.get_branches();
+    let branches = branches.lock().unwrap();
+    let head = branches[0].get_head();
+    let head = head.lock().unwrap();
+    assert_eq!(head.get_hash(), &ids[c1.get_hash()]);
+    assert_eq!(head.get_timezone(), "+0530");
+    assert_eq!(head.get_signature(), c1.get_signature());
+    let tags = back.get_tags();
+    let tags = tags.lock().unwrap();
+    assert_eq!(tags[0].get_annotation().unwrap().get_timezone(), "+0530");
+    drop((head, branches, tags));
+    assert_eq!(
+        back.save_git(&dir).unwrap()[&ids[c1.get_hash()]],
+        ids[c1.get_hash()]
+    );
+    git(&dir, &["fsck", "--strict"]);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,129 @@
+use gitlike::repository::*;
+use std::path::Path;
+use std::process::Command;
+
+fn run(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_AUTHOR_DATE", "1700000000 +0000")
+        .env("GIT_COMMITTER_DATE", "1700000000 +0000")
+        .env("GIT_AUTHOR_NAME", "A U Thor")
+        .env("GIT_AUTHOR_EMAIL", "a@x")
+        .env("GIT_COMMITTER_NAME", "A U Thor")
+        .env("GIT_COMMITTER_EMAIL", "a@x")
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+fn build(name: &str, gc: bool) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("git-read-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("src/deep")).unwrap();
+    run(&dir, &["init", "-q", "-b", "main"]);
+    let big: String = (0..2000).map(|i| format!("line {}\n", i)).collect();
+    std::fs::write(dir.join("big.txt"), &big).unwrap();
+    std::fs::write(dir.join("src/deep/a.rs"), "fn a() {}\n").unwrap();
+    run(&dir, &["add", "."]);
+    run(&dir, &["commit", "-qm", "first"]);
+    run(&dir, &["tag", "light"]);
+    std::fs::write(dir.join("big.txt"), format!("{}tail\n", big)).unwrap();
+    run(&dir, &["commit", "-qam", "second"]);
+    run(&dir, &["tag", "-a", "v1", "-m", "release one"]);
+    run(&dir, &["checkout", "-qb", "dev"]);
+    std::fs::write(dir.join("dev.txt"), "dev").unwrap();
+    run(&dir, &["add", "."]);
+    run(&dir, &["commit", "-qm", "dev work"]);
+    run(&dir, &["checkout", "-q", "main"]);
+    if gc {
+        run(&dir, &["gc", "-q", "--aggressive"]);
+    }
+    dir
+}
+
+fn check(dir: &Path) {
+    let repo = Repository::open_git(dir).unwrap();
+    assert_eq!(repo.get_head(), "main");
+    let branches = repo.get_branches();
+    let branches = branches.lock().unwrap();
+    assert_eq!(branches.len(), 2);
+    let main = branches.iter().find(|b| b.get_name() == "main").unwrap();
+    let head = main.get_head();
+    let head = head.lock().unwrap();
+    assert_eq!(head.get_message(), "second\n");
+    assert_eq!(head.get_author(), "A U Thor <a@x>");
+    assert_eq!(head.get_timestamp(), 1700000000);
+    assert_eq!(head.get_parents().len(), 1);
+    let snap = head.get_snapshot();
+    assert!(String::from_utf8(snap["big.txt"].clone())
+        .unwrap()
+        .ends_with("1999\ntail\n"));
+    assert_eq!(snap["src/deep/a.rs"], b"fn a() {}\n".to_vec());
+    let tags = repo.get_tags();
+    let tags = tags.lock().unwrap();
+    let v1 = tags.iter().find(|t| t.get_name() == "v1").unwrap();
+    assert_eq!(v1.get_message(), "release one\n");
+    let light = tags.iter().find(|t| t.get_name() == "light").unwrap();
+    assert_eq!(light.get_message(), "");
+    assert!(repo
+        .get_graph()
+        .lock()
+        .unwrap()
+        .contains(&head.get_parents()[0]));
+}
+
+#[test]
+fn loose() {
+    let dir = build("loose", false);
+    check(&dir);
+    check(&dir.join(".git"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn packed() {
+    let dir = build("packed", true);
+    assert!(dir.join(".git/packed-refs").exists());
+    let git = git::Git::open(&dir).unwrap();
+    assert!(git.refs().unwrap().contains_key("refs/tags/v1"));
+    check(&dir);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn malformed_pack() {
+    let dir = std::env::temp_dir().join(format!("git-read-malformed-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let git = git::Git::init(&dir).unwrap();
+    let id = "ab".repeat(20);
+    let mut index = vec![0u8; 256 * 4];
+    for slot in 0xab..256 {
+        index[slot * 4..slot * 4 + 4].copy_from_slice(&1u32.to_be_bytes());
+    }
+    index.extend_from_slice(&12u32.to_be_bytes());
+    index.extend_from_slice(&[0xab; 20]);
+    let mut pack = b"PACK\0\0\0\x02\0\0\0\x01".to_vec();
+    pack.extend_from_slice(&[0xbf; 16]);
+    pack.push(0x01);
+    let packs = git.get_root().join("objects").join("pack");
+    std::fs::write(packs.join("pack-bad.idx"), &index).unwrap();
+    std::fs::write(packs.join("pack-bad.pack"), &pack).unwrap();
+    let git = git::Git::open(&dir).unwrap();
+    assert!(git.has(&id));
+    assert!(git.read(&id).is_err());
+    pack.truncate(12);
+    pack.extend_from_slice(&[0xbf, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f, 0x78, 0x9c]);
+    std::fs::write(packs.join("pack-bad.pack"), &pack).unwrap();
+    assert!(git.read(&id).is_err());
+    pack.truncate(12);
+    pack.extend_from_slice(&[0x60, 0x00]);
+    std::fs::write(packs.join("pack-bad.pack"), &pack).unwrap();
+    assert!(git.read(&id).is_err());
+    pack.truncate(12);
+    pack.push(0x70);
+    pack.extend_from_slice(&[0xab; 20]);
+    std::fs::write(packs.join("pack-bad.pack"), &pack).unwrap();
+    assert!(git.read(&id).is_err());
+    let _ = std::fs::remove_dir_all(&dir);
+}
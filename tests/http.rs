@@ -0,0 +1,241 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+
+#[test]
+fn round_trip() {
+    let dir = std::env::temp_dir().join(format!("http-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let origin = Repository::new(dir.join("srv/proj").to_string_lossy().to_string());
+    origin.add_branch(Branch::new("main".into(), a.clone(), 1));
+    let b = origin.commit(mk(&[], 2, "b", &[("f", "2\n")])).unwrap();
+    origin.add_tag(Tag::new("v1".into(), &b, "release".into()), "alice", 2);
+    origin.save().unwrap();
+
+    let mut server = http::Server::bind("127.0.0.1:0", &dir.join("srv")).unwrap();
+    server.allow_receive("bob".into());
+    let url = format!("http://{}/proj", server.local_addr().unwrap());
+    std::thread::spawn(move || server.serve());
+
+    let local = Repository::new(dir.join("down").to_string_lossy().to_string());
+    local.add_branch(Branch::new("main".into(), a.clone(), 1));
+    local.add_remote(Remote::new("origin".into(), url.clone()));
+    let r = transport::fetch(&local, "origin", 3).unwrap();
+    assert!(r.is_ok());
+    assert_eq!(r.get_updates().len(), 2);
+    assert_eq!(local.get_tags().lock().unwrap()[0].get_message(), "release");
+    assert_eq!(r.get_objects(), 4);
+
+    let ctx = protection::Context::new("bob".into());
+    local
+        .update_ref("main", a.get_hash(), b.get_hash(), &ctx, 4)
+        .unwrap();
+    let c = local.commit(mk(&[], 5, "c", &[("f", "3\n")])).unwrap();
+    let p = transport::push(&local, "origin", &[], false, 6).unwrap();
+    assert!(p.is_ok(), "{:?}", p.get_rejected());
+    assert_eq!(p.get_objects(), 3);
+    let reopened = Repository::open(dir.join("srv/proj").to_string_lossy().to_string()).unwrap();
+    assert_eq!(reopened.resolve("main").unwrap(), *c.get_hash());
+
+    let bad = Remote::new("bad".into(), format!("{}/nope", url));
+    local.add_remote(bad);
+    assert!(matches!(
+        transport::fetch(&local, "bad", 7),
+        Err(transport::Error::Io(_))
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+fn serve(root: &std::path::Path) -> String {
+    let mut server = http::Server::bind("127.0.0.1:0", root).unwrap();
+    server.allow_receive("bob".into());
+    let address = server.local_addr().unwrap().to_string();
+    std::thread::spawn(move || server.serve());
+    address
+}
+
+fn exchange(address: &str, request: &[u8]) -> String {
+    use std::io::{Read, Write};
+    let mut stream = std::net::TcpStream::connect(address).unwrap();
+    stream.write_all(request).unwrap();
+    stream.shutdown(std::net::Shutdown::Write).unwrap();
+    let mut response = Vec::new();
+    let _ = stream.read_to_end(&mut response);
+    String::from_utf8_lossy(&response).to_string()
+}
+
+fn published(dir: &std::path::Path) -> Commit {
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let origin = Repository::new(dir.join("srv/proj").to_string_lossy().to_string());
+    origin.add_branch(Branch::new("main".into(), a.clone(), 1));
+    origin.save().unwrap();
+    a
+}
+
+#[test]
+fn rejects_escaping_refs_and_forged_objects() {
+    let dir = std::env::temp_dir().join(format!("http-escape-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let a = published(&dir);
+    let address = serve(&dir.join("srv"));
+    let client = http::Client::new(&format!("http://{}/proj", address)).unwrap();
+    let zero = "0".repeat(40);
+    let commands = vec![
+        store::Command::new(
+            zero.clone(),
+            a.get_hash().clone(),
+            "refs/../../escaped".into(),
+        ),
+        store::Command::new(zero.clone(), a.get_hash().clone(), "HEAD".into()),
+        store::Command::new(zero.clone(), a.get_hash().clone(), "refs/heads/ok".into()),
+    ];
+    let statuses = client.receive(&commands, &pack::Pack::new()).unwrap();
+    assert_eq!(statuses[0].1.as_deref(), Some("funny refname"));
+    assert_eq!(statuses[1].1.as_deref(), Some("funny refname"));
+    assert_eq!(statuses[2].1, None);
+    assert!(!dir.join("escaped").exists());
+
+    let mut forged = pack::Pack::new();
+    forged.add("blob", a.get_hash(), b"not the commit".to_vec());
+    forged.add("blob", "../../../outside", b"x".to_vec());
+    let command = store::Command::new(zero, a.get_hash().clone(), "refs/heads/forged".into());
+    assert!(client.receive(&[command], &forged).is_err());
+    let made_up = object::hash("commit", b"made up");
+    let body = format!(
+        "tree {}\nparent {}\nauthor mallory\ntimestamp 2\n\nforged",
+        a.get_tree_id(),
+        a.get_hash()
+    );
+    let mut forged = pack::Pack::new();
+    forged.add("commit", &made_up, body.into_bytes());
+    let command = store::Command::new(
+        a.get_hash().clone(),
+        made_up.clone(),
+        "refs/heads/main".into(),
+    );
+    assert!(client.receive(&[command], &forged).is_err());
+    let store = store::Store::new(&dir.join("srv/proj"));
+    assert!(!store.has(&made_up));
+    assert_eq!(store.read_refs().unwrap()["refs/heads/main"], *a.get_hash());
+    assert!(!store.read_refs().unwrap().contains_key("refs/heads/forged"));
+    assert!(store.read_commit(a.get_hash()).is_ok());
+    assert!(store.write_ref("refs/heads/../../x", a.get_hash()).is_err());
+    assert!(store.write_object("blob", "../x", b"x").is_err());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn survives_malformed_requests() {
+    let dir = std::env::temp_dir().join(format!("http-malformed-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let a = published(&dir);
+    let address = serve(&dir.join("srv"));
+    assert!(exchange(&address, b"garbage\r\n\r\n").starts_with("HTTP/1.1 400"));
+    assert!(exchange(
+        &address,
+        b"POST /proj/git-upload-pack HTTP/1.1\r\nContent-Length: nope\r\n\r\n"
+    )
+    .starts_with("HTTP/1.1 400"));
+    exchange(
+        &address,
+        b"POST /proj/git-upload-pack HTTP/1.1\r\nContent-Length: 4096\r\n\r\nshort",
+    );
+    assert!(exchange(
+        &address,
+        b"POST /proj/git-upload-pack HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n"
+    )
+    .starts_with("HTTP/1.1 400"));
+    assert!(exchange(
+        &address,
+        b"POST /proj/git-upload-pack HTTP/1.1\r\nContent-Length: 3\r\n\r\nabc"
+    )
+    .starts_with("HTTP/1.1 500"));
+
+    let local = Repository::new(dir.join("down").to_string_lossy().to_string());
+    local.add_remote(Remote::new(
+        "origin".into(),
+        format!("http://{}/proj", address),
+    ));
+    let report = transport::fetch(&local, "origin", 2).unwrap();
+    assert_eq!(report.get_updates().len(), 1);
+    let tracked = local.get_remotes();
+    let tracked = tracked.lock().unwrap();
+    let branches = tracked[0].get_branches();
+    assert_eq!(
+        branches.lock().unwrap()[0]
+            .get_head()
+            .lock()
+            .unwrap()
+            .get_hash(),
+        a.get_hash()
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn accepts_chunked_bodies() {
+    let dir = std::env::temp_dir().join(format!("http-chunked-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let a = published(&dir);
+    let address = serve(&dir.join("srv"));
+    let command = store::Command::new(
+        "0".repeat(40),
+        a.get_hash().clone(),
+        "refs/heads/side".into(),
+    );
+    let body = protocol::receive_request(&[command], &pack::Pack::new());
+    let (first, second) = body.split_at(body.len() / 2);
+    let mut request =
+        b"POST /proj/git-receive-pack HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n".to_vec();
+    for chunk in [first, second] {
+        request.extend(format!("{:x}\r\n", chunk.len()).as_bytes());
+        request.extend(chunk);
+        request.extend(b"\r\n");
+    }
+    request.extend(b"0\r\n\r\n");
+    assert!(exchange(&address, &request).starts_with("HTTP/1.1 200"));
+    let store = store::Store::new(&dir.join("srv/proj"));
+    assert_eq!(store.read_refs().unwrap()["refs/heads/side"], *a.get_hash());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn pushes_are_opt_in_and_bodies_are_capped() {
+    let dir = std::env::temp_dir().join(format!("http-guard-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let a = published(&dir);
+    let mut server = http::Server::bind("127.0.0.1:0", &dir.join("srv")).unwrap();
+    server.set_limit(64);
+    let address = server.local_addr().unwrap().to_string();
+    std::thread::spawn(move || server.serve());
+
+    let idle = std::net::TcpStream::connect(&address).unwrap();
+    let local = Repository::new(dir.join("down").to_string_lossy().to_string());
+    local.add_branch(Branch::new("main".into(), a.clone(), 1));
+    local.add_remote(Remote::new(
+        "origin".into(),
+        format!("http://{}/proj", address),
+    ));
+    assert!(transport::fetch(&local, "origin", 2).unwrap().is_ok());
+    drop(idle);
+
+    local.commit(mk(&[], 3, "b", &[("f", "2\n")])).unwrap();
+    assert!(matches!(
+        transport::push(&local, "origin", &[], false, 4),
+        Err(transport::Error::Io(why)) if why.contains("403")
+    ));
+    let store = store::Store::new(&dir.join("srv/proj"));
+    assert_eq!(store.read_refs().unwrap()["refs/heads/main"], *a.get_hash());
+    assert!(exchange(
+        &address,
+        b"POST /proj/git-upload-pack HTTP/1.1\r\nContent-Length: 65\r\n\r\n"
+    )
+    .starts_with("HTTP/1.1 413"));
+    assert!(exchange(
+        &address,
+        b"POST /proj/git-upload-pack HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n41\r\n"
+    )
+    .starts_with("HTTP/1.1 413"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
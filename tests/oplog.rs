@@ -0,0 +1,234 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+
+#[test]
+fn oplog_flow() {
+    let a = mk(&[], 1, "init", &[("f", "1\n2\n3\n")]);
+    let repo = Repository::new("/tmp/r".into());
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    repo.add_branch(Branch::new("dev".into(), a.clone(), 1));
+    let b = repo
+        .commit(mk(&[], 2, "second", &[("f", "ONE\n2\n3\n")]))
+        .unwrap();
+    repo.checkout("dev", "bob", 3);
+    repo.commit(mk(&[], 3, "third", &[("f", "1\n2\nTHREE\n")]))
+        .unwrap();
+    repo.checkout("main", "bob", 4);
+    let m = repo.merge("dev", "bob", 5).unwrap();
+    assert_eq!(text(&m, "f"), "ONE\n2\nTHREE\n");
+    repo.add_tag(Tag::new("v1".into(), &m, "rel".into()), "bob", 6);
+    assert_eq!(repo.get_oplog().lock().unwrap().get_operations().len(), 8);
+    let op = repo.undo("bob", 7).unwrap().unwrap();
+    assert_eq!(op.get_description(), "tag: v1");
+    assert!(repo.get_tags().lock().unwrap().is_empty());
+    repo.undo("bob", 7).unwrap().unwrap();
+    assert_eq!(repo.resolve("main").unwrap(), *b.get_hash());
+    repo.redo("bob", 8).unwrap().unwrap();
+    assert_eq!(repo.resolve("main").unwrap(), *m.get_hash());
+    for _ in 0..5 {
+        repo.undo("bob", 9).unwrap().unwrap();
+    }
+    assert_eq!(repo.get_branches().lock().unwrap().len(), 2);
+    repo.undo("bob", 9).unwrap().unwrap();
+    assert_eq!(repo.get_branches().lock().unwrap().len(), 1);
+    assert!(repo.undo("bob", 9).unwrap().is_some());
+    assert!(repo.get_branches().lock().unwrap().is_empty());
+    for _ in 0..8 {
+        repo.redo("bob", 9).unwrap().unwrap();
+    }
+    assert_eq!(repo.get_tags().lock().unwrap().len(), 1);
+    assert_eq!(repo.resolve("main").unwrap(), *m.get_hash());
+    assert_eq!(repo.get_head(), "main");
+}
+
+#[test]
+fn redo_restores_commits() {
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let repo = Repository::new("/tmp/r".into());
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    let b = repo.commit(mk(&[], 2, "second", &[("f", "2\n")])).unwrap();
+    let branches = repo.get_branches();
+    let commits = |repo: &Repository| {
+        let branches = repo.get_branches();
+        let branches = branches.lock().unwrap();
+        let commits = branches[0].get_commits();
+        let hashes: Vec<String> = commits
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|c| c.get_hash().clone())
+            .collect();
+        hashes
+    };
+    drop(branches);
+    assert_eq!(commits(&repo), vec![b.get_hash().clone()]);
+    repo.undo("bob", 3).unwrap().unwrap();
+    assert!(commits(&repo).is_empty());
+    repo.redo("bob", 4).unwrap().unwrap();
+    assert_eq!(commits(&repo), vec![b.get_hash().clone()]);
+    assert_eq!(repo.resolve("main").unwrap(), *b.get_hash());
+}
+
+#[test]
+fn undo_delete_keeps_branch_state() {
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let repo = Repository::new("/tmp/r".into());
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    repo.add_branch(Branch::new("topic".into(), a.clone(), 1));
+    let mut origin = Remote::new("origin".into(), "/tmp/none".into());
+    origin
+        .get_branches()
+        .lock()
+        .unwrap()
+        .push(Branch::new("topic".into(), a.clone(), 1));
+    origin.set_default(None);
+    repo.add_remote(origin);
+    repo.set_upstream(
+        "topic",
+        Some(tracking::Upstream::new("origin".into(), "topic".into())),
+    );
+    repo.checkout("topic", "bob", 2);
+    let b = repo.commit(mk(&[], 3, "work", &[("f", "2\n")])).unwrap();
+    repo.checkout("main", "bob", 4);
+    repo.delete_branch("topic", true, "bob", 5).unwrap();
+    repo.undo("bob", 6).unwrap().unwrap();
+    let branches = repo.get_branches();
+    let branches = branches.lock().unwrap();
+    let topic = branches.iter().find(|b| b.get_name() == "topic").unwrap();
+    assert_eq!(topic.get_head().lock().unwrap().get_hash(), b.get_hash());
+    assert_eq!(topic.get_commits().lock().unwrap().len(), 1);
+    assert!(topic.get_upstream().is_some());
+    let reflog = topic.get_reflog();
+    let reasons: Vec<String> = reflog
+        .lock()
+        .unwrap()
+        .get_entries()
+        .iter()
+        .map(|e| e.get_reason().clone())
+        .collect();
+    assert_eq!(
+        reasons,
+        vec![
+            "branch: Created from ".to_string() + a.get_hash(),
+            "commit: work".into()
+        ]
+    );
+}
+
+#[test]
+fn pick_rebase_and_fetch_are_recorded() {
+    let a = mk(&[], 1, "init", &[("f", "1\n2\n3\n")]);
+    let repo = Repository::new("/tmp/r".into());
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    repo.add_branch(Branch::new("topic".into(), a.clone(), 1));
+    let b = repo
+        .commit(mk(&[], 2, "main", &[("f", "ONE\n2\n3\n")]))
+        .unwrap();
+    repo.checkout("topic", "bob", 3);
+    let c = repo
+        .commit(mk(&[], 4, "topic", &[("f", "1\n2\nTHREE\n")]))
+        .unwrap();
+    repo.checkout("main", "bob", 5);
+    let picked = repo.pick(c.get_hash(), "bob", 6).unwrap();
+    assert_eq!(text(&picked, "f"), "ONE\n2\nTHREE\n");
+    let op = repo.undo("bob", 7).unwrap().unwrap();
+    assert_eq!(
+        op.get_description(),
+        &format!("cherry-pick: {}", c.get_hash())
+    );
+    assert_eq!(repo.resolve("main").unwrap(), *b.get_hash());
+
+    let mut session = {
+        let graph = repo.get_graph();
+        let graph = graph.lock().unwrap();
+        let branches = repo.get_branches();
+        let branches = branches.lock().unwrap();
+        let topic = branches.iter().find(|x| x.get_name() == "topic").unwrap();
+        rebase::Session::new(
+            &graph,
+            topic,
+            b.get_hash(),
+            rebase::todo(&graph, a.get_hash(), c.get_hash()),
+        )
+        .unwrap()
+    };
+    let rebased = repo.rebase(&mut session, "bob", 8).unwrap();
+    assert_eq!(repo.resolve("topic").unwrap(), *rebased.get_hash());
+    assert_eq!(
+        repo.undo("bob", 9).unwrap().unwrap().get_description(),
+        "rebase: topic"
+    );
+    assert_eq!(repo.resolve("topic").unwrap(), *c.get_hash());
+
+    let dir = std::env::temp_dir().join(format!("oplog-fetch-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let up = dir.join("up").to_string_lossy().to_string();
+    let origin = Repository::new(up.clone());
+    origin.add_branch(Branch::new("main".into(), a.clone(), 1));
+    origin.add_tag(Tag::new("v1".into(), &a, "rel".into()), "bob", 1);
+    origin.save().unwrap();
+    repo.add_remote(Remote::new("origin".into(), up));
+    let report = transport::fetch(&repo, "origin", 10).unwrap();
+    assert!(report.is_ok());
+    assert_eq!(repo.get_tags().lock().unwrap().len(), 1);
+    assert_eq!(
+        repo.undo("bob", 11).unwrap().unwrap().get_description(),
+        "fetch: origin"
+    );
+    assert!(repo.get_tags().lock().unwrap().is_empty());
+    assert_eq!(repo.get_branches().lock().unwrap().len(), 2);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn concurrent_operations_do_not_deadlock() {
+    let a = mk(&[], 1, "init", &[("f", "0\n")]);
+    let dir = std::env::temp_dir().join(format!("oplog-threads-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = dir.to_string_lossy().to_string();
+    let seed = Repository::new(path.clone());
+    seed.add_branch(Branch::new("main".into(), a.clone(), 1));
+    seed.save().unwrap();
+    let repo = std::sync::Arc::new(Repository::open(path).unwrap());
+    let (done, finished) = std::sync::mpsc::channel();
+    let mut workers = Vec::new();
+    for worker in 0..4u64 {
+        let repo = std::sync::Arc::clone(&repo);
+        let done = done.clone();
+        workers.push(std::thread::spawn(move || {
+            for i in 0..200u64 {
+                let ts = 10 + worker * 1000 + i;
+                match worker {
+                    0 => {
+                        let _ = repo.commit(mk(&[], ts, "c", &[("f", &format!("{}\n", ts))]));
+                    }
+                    1 => {
+                        let _ = repo.undo("bob", ts);
+                    }
+                    2 => {
+                        let _ = repo.redo("bob", ts);
+                    }
+                    _ => {
+                        let tag = format!("t{}", ts);
+                        let head = repo.resolve("main").unwrap();
+                        let commit = repo.get_graph().lock().unwrap().get_commit(&head).cloned();
+                        if let Some(commit) = commit {
+                            repo.add_tag(Tag::new(tag, &commit, "m".into()), "bob", ts);
+                        }
+                    }
+                }
+            }
+            done.send(()).unwrap();
+        }));
+    }
+    for _ in 0..4 {
+        finished
+            .recv_timeout(std::time::Duration::from_secs(60))
+            .expect("deadlock");
+    }
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,89 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+
+#[test]
+fn pick_revert() {
+    let mut g = graph::Graph::new();
+    let a = mk(&[], 1, "init", &[("f", "1\n2\n3\n4\n5\n")]);
+    let b = mk(
+        &[a.get_hash()],
+        2,
+        "change 5",
+        &[("f", "1\n2\n3\n4\nFIVE\n")],
+    );
+    let c = mk(
+        &[a.get_hash()],
+        3,
+        "change 1",
+        &[("f", "ONE\n2\n3\n4\n5\n")],
+    );
+    g.add_commit(a.clone());
+    g.add_commit(b.clone());
+    g.add_commit(c.clone());
+    let br = Branch::new("main".into(), c.clone(), 3);
+    let n = pick::cherry_pick(&mut g, &br, b.get_hash(), "bob", 4).unwrap();
+    assert_eq!(text(&n, "f"), "ONE\n2\n3\n4\nFIVE\n");
+    assert!(n
+        .get_message()
+        .ends_with(&format!("(cherry picked from commit {})", b.get_hash())));
+    assert_eq!(n.get_parents(), &vec![c.get_hash().clone()]);
+    let r = pick::revert(&mut g, &br, c.get_hash(), "bob", 5).unwrap();
+    assert_eq!(text(&r, "f"), "1\n2\n3\n4\nFIVE\n");
+    assert!(r.get_message().starts_with("Revert \"change 1\""));
+    let d = mk(
+        &[a.get_hash()],
+        6,
+        "conflict",
+        &[("f", "1\n2\n3\n4\nfive!\n")],
+    );
+    g.add_commit(d.clone());
+    match pick::cherry_pick(&mut g, &br, d.get_hash(), "bob", 7) {
+        Err(pick::Error::Conflict(cf)) => {
+            assert_eq!(cf.get_paths(), &vec!["f".to_string()]);
+            let t = String::from_utf8(cf.get_snapshot()["f"].clone()).unwrap();
+            assert!(
+                t.contains("<<<<<<< HEAD\nFIVE\n=======\nfive!\n>>>>>>>"),
+                "{t}"
+            );
+            let res = cf
+                .resolve(&mut g, &br, snap(&[("f", "x\n")]), "bob", 8)
+                .unwrap();
+            assert_eq!(br.get_head().lock().unwrap().get_hash(), res.get_hash());
+        }
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn merge_lines() {
+    let s = |x: &str| x.split(' ').map(String::from).collect::<Vec<_>>();
+    assert_eq!(
+        merge::lines(&s("a b c d"), &s("a X b c d"), &s("a b c Y"), ("o", "t")).unwrap(),
+        s("a X b c Y")
+    );
+    assert_eq!(
+        merge::lines(&s("a b c"), &s("a b c z"), &s("a b c z"), ("o", "t")).unwrap(),
+        s("a b c z")
+    );
+    assert!(merge::lines(&s("a b c"), &s("a B c"), &s("a b2 c"), ("o", "t")).is_err());
+}
+
+#[test]
+fn commits_onto_head_are_rehashed() {
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let br = Branch::new("main".into(), a.clone(), 1);
+    let b = mk(&[], 2, "next", &[("f", "2\n")]);
+    br.add_commit(b.clone()).unwrap();
+    let head = br.get_head().lock().unwrap().clone();
+    assert_eq!(head.get_parents(), &vec![a.get_hash().clone()]);
+    let expected = object::commit_hash(
+        &snap(&[("f", "2\n")]),
+        &[a.get_hash().clone()],
+        "alice",
+        "next",
+        2,
+    );
+    assert_eq!(*head.get_hash(), expected);
+    assert_ne!(head.get_hash(), b.get_hash());
+}
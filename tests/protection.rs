@@ -0,0 +1,166 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+
+fn guarded(pattern: &str, approvals: u32) -> protection::Rule {
+    let mut rule = protection::Rule::new(pattern.into());
+    rule.set_approvals(approvals);
+    rule
+}
+
+fn approved(actor: &str, approvals: u32) -> protection::Context {
+    let mut context = protection::Context::new(actor.into());
+    context.set_approvals(approvals);
+    context
+}
+
+#[test]
+fn protection_flow() {
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let repo = Repository::new("/tmp/r".into());
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    let mut rule = protection::Rule::new("ma*".into());
+    rule.set_linear(true);
+    rule.set_approvals(2);
+    rule.add_check("ci".into());
+    rule.add_pusher("alice".into());
+    repo.protect(rule);
+    let c = mk(&[], 2, "x", &[("f", "2\n")]);
+    assert_eq!(
+        repo.commit(c.clone()).err().unwrap(),
+        branch::Rejection::Anonymous("main".into())
+    );
+    let mut ctx = approved("alice", 2);
+    assert_eq!(
+        repo.commit_with(c.clone(), &ctx).err().unwrap(),
+        branch::Rejection::Checks(vec!["ci".into()])
+    );
+    ctx.add_check("ci".into());
+    repo.commit_with(c.clone(), &ctx).unwrap();
+    let bob = protection::Context::new("bob".into());
+    assert_eq!(
+        repo.commit_with(c.clone(), &bob).err().unwrap(),
+        branch::Rejection::Pusher("bob".into())
+    );
+    let rewrite = mk(&[a.get_hash()], 3, "y", &[("f", "3\n")]);
+    assert_eq!(
+        repo.commit_with(rewrite, &ctx).err().unwrap(),
+        branch::Rejection::Rewrite("main".into())
+    );
+}
+
+#[test]
+fn newest_rule_wins() {
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let repo = Repository::new("/tmp/r".into());
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    repo.protect(guarded("rel*", 1));
+    repo.protect(guarded("release/*", 3));
+    repo.add_branch(Branch::new("release/1".into(), a.clone(), 1));
+    repo.checkout("release/1", "alice", 2);
+    let c = mk(&[], 2, "x", &[("f", "2\n")]);
+    assert_eq!(
+        repo.commit_with(c.clone(), &approved("alice", 1))
+            .err()
+            .unwrap(),
+        branch::Rejection::Approvals(3, 1)
+    );
+    repo.add_branch(Branch::new("topic".into(), a.clone(), 1));
+    repo.rename_branch("topic", "release/2", "alice", 3)
+        .unwrap();
+    repo.checkout("release/2", "alice", 4);
+    assert_eq!(
+        repo.commit_with(c, &approved("alice", 1)).err().unwrap(),
+        branch::Rejection::Approvals(3, 1)
+    );
+}
+
+#[test]
+fn merge_and_pick_take_context() {
+    let a = mk(&[], 1, "init", &[("f", "1\n"), ("g", "1\n")]);
+    let repo = Repository::new("/tmp/r".into());
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    repo.add_branch(Branch::new("feat".into(), a.clone(), 1));
+    repo.checkout("feat", "alice", 2);
+    repo.commit(mk(&[], 3, "b", &[("f", "2\n"), ("g", "1\n")]))
+        .unwrap();
+    let c = repo
+        .commit(mk(&[], 4, "c", &[("f", "2\n"), ("g", "2\n")]))
+        .unwrap();
+    repo.checkout("main", "alice", 5);
+    repo.protect(guarded("main", 2));
+    assert!(matches!(
+        repo.pick(c.get_hash(), "alice", 6),
+        Err(pick::Error::Rejected(branch::Rejection::Approvals(2, 0)))
+    ));
+    let picked = repo
+        .pick_with(c.get_hash(), &approved("alice", 2), 6)
+        .unwrap();
+    assert_eq!(text(&picked, "g"), "2\n");
+    assert!(matches!(
+        repo.merge("feat", "alice", 7),
+        Err(merge::Error::Rejected(branch::Rejection::Approvals(2, 0)))
+    ));
+    let merged = repo.merge_with("feat", &approved("alice", 2), 7).unwrap();
+    assert!(merged.get_parents().contains(c.get_hash()));
+    assert_eq!(text(&merged, "f"), "2\n");
+    assert_eq!(repo.resolve("main").unwrap(), *merged.get_hash());
+}
+
+#[test]
+fn undo_respects_protection() {
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let repo = Repository::new("/tmp/r".into());
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    let b = repo.commit(mk(&[], 2, "b", &[("f", "2\n")])).unwrap();
+    repo.protect(protection::Rule::new("main".into()));
+    assert_eq!(
+        repo.undo("bob", 3).err().unwrap(),
+        branch::Rejection::Rewrite("main".into())
+    );
+    assert_eq!(repo.resolve("main").unwrap(), *b.get_hash());
+    let mut open = protection::Rule::new("main".into());
+    open.set_rewrite(true);
+    repo.protect(open);
+    assert!(repo.undo("bob", 4).unwrap().is_some());
+    assert_eq!(repo.resolve("main").unwrap(), *a.get_hash());
+}
+
+#[test]
+fn fetch_respects_protection() {
+    let dir = std::env::temp_dir().join(format!("protection-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let up = dir.join("up").to_string_lossy().to_string();
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let origin = Repository::new(up.clone());
+    origin.add_branch(Branch::new(
+        "main".into(),
+        mk(&[], 1, "x", &[("f", "9\n")]),
+        1,
+    ));
+    origin.save().unwrap();
+
+    let local = Repository::new(dir.join("down").to_string_lossy().to_string());
+    local.add_branch(Branch::new("dev".into(), a.clone(), 1));
+    local.add_branch(Branch::new("main".into(), a.clone(), 1));
+    local.protect(protection::Rule::new("main".into()));
+    let mut remote = Remote::new("origin".into(), up);
+    remote
+        .set_fetch(&["+refs/heads/main:refs/heads/main"])
+        .unwrap();
+    local.add_remote(remote);
+    let report = transport::fetch(&local, "origin", 2).unwrap();
+    assert!(report
+        .get_rejected()
+        .iter()
+        .any(|(r, why)| r == "refs/heads/main"
+            && *why == branch::Rejection::Anonymous("main".into())));
+    let mut options = transport::Options::new();
+    options.set_actor(Some("alice".into()));
+    let report = transport::fetch_with(&local, "origin", &options, 3).unwrap();
+    assert!(report.get_rejected().iter().any(
+        |(r, why)| r == "refs/heads/main" && *why == branch::Rejection::Rewrite("main".into())
+    ));
+    assert_eq!(local.resolve("main").unwrap(), *a.get_hash());
+    let _ = std::fs::remove_dir_all(&dir);
+}
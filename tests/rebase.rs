@@ -0,0 +1,131 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+use rebase::*;
+
+#[test]
+fn rebase_flow() {
+    let mut g = graph::Graph::new();
+    let a = mk(&[], 1, "init", &[("f", "1\n2\n3\n4\n5\n6\n7\n")]);
+    let u = mk(
+        &[a.get_hash()],
+        2,
+        "upstream",
+        &[("f", "1\n2\n3\n4\n5\n6\nSEVEN\n")],
+    );
+    let b = mk(
+        &[a.get_hash()],
+        3,
+        "feat one",
+        &[("f", "ONE\n2\n3\n4\n5\n6\n7\n")],
+    );
+    let c = mk(
+        &[b.get_hash()],
+        4,
+        "feat three",
+        &[("f", "ONE\n2\nTHREE\n4\n5\n6\n7\n")],
+    );
+    let d = mk(
+        &[c.get_hash()],
+        5,
+        "fixup! feat one",
+        &[("f", "ONE!\n2\nTHREE\n4\n5\n6\n7\n")],
+    );
+    for x in [&a, &u, &b, &c, &d] {
+        g.add_commit(x.clone());
+    }
+    let br = Branch::new("topic".into(), d.clone(), 5);
+    let steps = todo(&g, u.get_hash(), d.get_hash());
+    assert_eq!(steps.len(), 3);
+    let steps = autosquash(&g, steps);
+    assert_eq!(steps[1], Step::Fixup(d.get_hash().clone()));
+    let mut steps = steps;
+    steps.push(Step::Exec("true".into()));
+    let mut s = Session::new(&g, &br, u.get_hash(), steps).unwrap();
+    let head = s.run(&mut g, &br).unwrap();
+    assert_eq!(text(&head, "f"), "ONE!\n2\nTHREE\n4\n5\n6\nSEVEN\n");
+    assert_eq!(g.range(u.get_hash(), head.get_hash()).len(), 2);
+    assert_eq!(br.get_head().lock().unwrap().get_hash(), head.get_hash());
+    let first = g
+        .get_commit(&g.range(u.get_hash(), head.get_hash())[0])
+        .unwrap();
+    assert_eq!(first.get_message(), "feat one");
+
+    let e = mk(
+        &[a.get_hash()],
+        6,
+        "touch seven",
+        &[("f", "1\n2\n3\n4\n5\n6\nseven?\n")],
+    );
+    g.add_commit(e.clone());
+    let br2 = Branch::new("t2".into(), e.clone(), 6);
+    let mut s = Session::new(
+        &g,
+        &br2,
+        u.get_hash(),
+        vec![Step::Reword(e.get_hash().clone(), "renamed".into())],
+    )
+    .unwrap();
+    match s.run(&mut g, &br2) {
+        Err(Stop::Conflict(p)) => assert_eq!(p, vec!["f".to_string()]),
+        _ => panic!(),
+    }
+    s.resolve(&mut g, snap(&[("f", "resolved\n")])).unwrap();
+    assert!(matches!(
+        s.resolve(&mut g, snap(&[("f", "again\n")])),
+        Err(Stop::Clean)
+    ));
+    let h = s.run(&mut g, &br2).unwrap();
+    assert_eq!(h.get_message(), "renamed");
+    assert_eq!(h.get_parents(), &vec![u.get_hash().clone()]);
+}
+
+#[test]
+fn rebase_invalid_todo() {
+    let mut g = graph::Graph::new();
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let b = mk(&[a.get_hash()], 2, "two", &[("f", "2\n")]);
+    let c = mk(&[b.get_hash()], 3, "three", &[("f", "3\n")]);
+    for x in [&a, &b, &c] {
+        g.add_commit(x.clone());
+    }
+    let br = Branch::new("topic".into(), c.clone(), 3);
+    let steps = vec![
+        Step::Fixup(b.get_hash().clone()),
+        Step::Pick(c.get_hash().clone()),
+    ];
+    let mut s = Session::new(&g, &br, a.get_hash(), steps).unwrap();
+    match s.run(&mut g, &br) {
+        Err(Stop::Invalid(step)) => assert_eq!(step, Step::Fixup(b.get_hash().clone())),
+        other => panic!("{:?}", other.map(|c| c.get_hash().clone())),
+    }
+    assert_eq!(s.get_remaining().len(), 2);
+    assert_eq!(br.get_head().lock().unwrap().get_hash(), c.get_hash());
+
+    let steps = vec![
+        Step::Drop(b.get_hash().clone()),
+        Step::Squash(c.get_hash().clone()),
+    ];
+    let mut s = Session::new(&g, &br, a.get_hash(), steps).unwrap();
+    assert!(matches!(s.run(&mut g, &br), Err(Stop::Invalid(_))));
+
+    let steps = vec![
+        Step::Pick("missing".into()),
+        Step::Pick(b.get_hash().clone()),
+    ];
+    let mut s = Session::new(&g, &br, a.get_hash(), steps).unwrap();
+    match s.run(&mut g, &br) {
+        Err(Stop::Missing(hash)) => assert_eq!(hash, "missing"),
+        other => panic!("{:?}", other.map(|c| c.get_hash().clone())),
+    }
+    assert_eq!(br.get_head().lock().unwrap().get_hash(), c.get_hash());
+    s.skip();
+    assert_eq!(s.get_remaining(), vec![Step::Pick(b.get_hash().clone())]);
+    let head = s.run(&mut g, &br).unwrap();
+    assert_eq!(head.get_hash(), b.get_hash());
+
+    assert!(matches!(
+        Session::new(&g, &br, "nowhere", Vec::new()),
+        Err(Stop::Missing(hash)) if hash == "nowhere"
+    ));
+}
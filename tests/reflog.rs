@@ -0,0 +1,34 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+
+#[test]
+fn reflog_flow() {
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let repo = Repository::new("/tmp/r".into());
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    repo.add_branch(Branch::new("dev".into(), a.clone(), 1));
+    let b = repo
+        .commit(mk(&[], 2, "second\nbody", &[("f", "2\n")]))
+        .unwrap();
+    let c = repo.commit(mk(&[], 3, "third", &[("f", "3\n")])).unwrap();
+    assert_eq!(repo.resolve("main@{0}").unwrap(), *c.get_hash());
+    assert_eq!(repo.resolve("main@{1}").unwrap(), *b.get_hash());
+    assert_eq!(repo.resolve("main@{2}").unwrap(), *a.get_hash());
+    assert_eq!(repo.resolve("HEAD@{1}").unwrap(), *b.get_hash());
+    assert_eq!(repo.resolve("main@{t2}").unwrap(), *b.get_hash());
+    assert_eq!(repo.resolve("main").unwrap(), *c.get_hash());
+    assert_eq!(repo.resolve(&c.get_hash()[..8]).unwrap(), *c.get_hash());
+    repo.checkout("dev", "bob", 4);
+    assert_eq!(repo.resolve("HEAD").unwrap(), *a.get_hash());
+    let branches = repo.get_branches();
+    let b0 = &branches.lock().unwrap()[0];
+    let log = b0.get_reflog();
+    let mut log = log.lock().unwrap();
+    assert_eq!(log.get(0).unwrap().get_reason(), "commit: third");
+    let ex = log.expire(&reflog::Policy::new(Some(1), None), 3);
+    assert_eq!(ex.len(), 1);
+    let ex = log.expire(&reflog::Policy::new(None, Some(1)), 3);
+    assert_eq!(ex.len(), 1);
+    assert_eq!(log.get_entries().len(), 1);
+}
@@ -0,0 +1,104 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+
+#[test]
+fn parse() {
+    let s = refspec::Refspec::parse("+refs/heads/*:refs/remotes/o/*").unwrap();
+    assert!(s.is_force());
+    assert_eq!(s.map("refs/heads/a/b").unwrap(), "refs/remotes/o/a/b");
+    assert_eq!(
+        refspec::Refspec::parse("refs/heads/*:refs/x"),
+        Err(refspec::Error::Wildcard("refs/heads/*:refs/x".into()))
+    );
+    assert!(refspec::Refspec::parse("^+refs/heads/x").is_err());
+    assert!(refspec::Refspec::parse("refs/he ads").is_err());
+    let specs = vec![
+        refspec::Refspec::parse("refs/heads/*:refs/remotes/o/*").unwrap(),
+        refspec::Refspec::parse("^refs/heads/wip/*").unwrap(),
+    ];
+    assert_eq!(refspec::resolve(&specs, "refs/heads/wip/x"), None);
+    assert_eq!(
+        refspec::resolve(&specs, "refs/heads/m"),
+        Some(("refs/remotes/o/m".into(), false))
+    );
+}
+
+#[test]
+fn routed() {
+    let dir = std::env::temp_dir().join(format!("t038-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let up = dir.join("up").to_string_lossy().to_string();
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let origin = Repository::new(up.clone());
+    origin.add_branch(Branch::new("main".into(), a.clone(), 1));
+    origin.add_branch(Branch::new("wip/x".into(), a.clone(), 1));
+    origin.add_tag(Tag::new("v1".into(), &a, "one".into()), "alice", 1);
+    origin.save().unwrap();
+
+    let local = Repository::new(dir.join("down").to_string_lossy().to_string());
+    local.add_branch(Branch::new("dev".into(), mk(&[], 1, "d", &[("g", "1")]), 1));
+    let mut remote = Remote::new("origin".into(), up.clone());
+    remote.add_fetch("^refs/heads/wip/*").unwrap();
+    remote
+        .add_push("refs/heads/dev:refs/heads/review/dev")
+        .unwrap();
+    local.add_remote(remote);
+    let r = transport::fetch(&local, "origin", 2).unwrap();
+    let refs: Vec<_> = r
+        .get_updates()
+        .iter()
+        .map(|u| u.get_reference().clone())
+        .collect();
+    assert_eq!(refs, vec!["refs/remotes/origin/main", "refs/tags/v1"]);
+    assert_eq!(local.get_tags().lock().unwrap()[0].get_message(), "one");
+    let p = transport::push(&local, "origin", &[], false, 3).unwrap();
+    assert_eq!(p.get_updates()[0].get_reference(), "refs/heads/review/dev");
+    let reopened = Repository::open(up).unwrap();
+    assert!(reopened.resolve("review/dev").is_some());
+    assert_eq!(reopened.get_tags().lock().unwrap().len(), 1);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn fetched_heads_respect_archive_and_rules() {
+    let dir = std::env::temp_dir().join(format!("refspec-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let up = dir.join("up").to_string_lossy().to_string();
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let origin = Repository::new(up.clone());
+    origin.add_branch(Branch::new("old".into(), a.clone(), 1));
+    origin.add_branch(Branch::new("release/1".into(), a.clone(), 1));
+    origin.save().unwrap();
+
+    let local = Repository::new(dir.join("down").to_string_lossy().to_string());
+    local.add_branch(Branch::new("dev".into(), mk(&[], 1, "d", &[("g", "1")]), 1));
+    local.add_branch(Branch::new("old".into(), mk(&[], 1, "o", &[("g", "2")]), 1));
+    local.archive("old", "bob", 2);
+    let mut rule = protection::Rule::new("release/*".into());
+    rule.set_approvals(1);
+    local.protect(rule);
+    let mut remote = Remote::new("origin".into(), up);
+    remote.set_fetch(&["+refs/heads/*:refs/heads/*"]).unwrap();
+    local.add_remote(remote);
+    let r = transport::fetch(&local, "origin", 3).unwrap();
+    assert_eq!(
+        r.get_rejected(),
+        &vec![(
+            "refs/heads/old".to_string(),
+            branch::Rejection::Archived("old".into())
+        )]
+    );
+    assert!(local.resolve("old").is_none());
+    local.checkout("release/1", "bob", 4);
+    assert_eq!(
+        local
+            .commit_with(
+                mk(&[], 5, "x", &[("f", "2\n")]),
+                &protection::Context::new("bob".into())
+            )
+            .err(),
+        Some(branch::Rejection::Approvals(1, 0))
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,93 @@
+mod common;
+use common::*;
+use gitlike::repository::release::{Entry, Kind, Notes, Reference};
+use gitlike::repository::*;
+
+#[test]
+fn notes() {
+    let repo = Repository::new("mem".into());
+    let a = mk(&[], 1, "chore: init", &[("f", "1")]);
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    let msgs = [
+        "feat(api): add search (#45)",
+        "fix: crash on empty input\n\nFixes #12",
+        "refactor!: drop v1 endpoints",
+        "feat: new config\n\nBREAKING CHANGE: config moved to toml",
+        "Update README, see #7 and #7",
+    ];
+    let mut prev = a.clone();
+    for (i, m) in msgs.iter().enumerate() {
+        prev = repo
+            .commit(mk(
+                &[prev.get_hash()],
+                2 + i as u64,
+                m,
+                &[("f", &i.to_string())],
+            ))
+            .unwrap();
+    }
+    repo.add_tag(Tag::new("v1.0.0".into(), &a, "one".into()), "alice", 10);
+    repo.add_tag(Tag::new("v2.0.0".into(), &prev, "two".into()), "alice", 10);
+    let tags = repo.get_tags().lock().unwrap().clone();
+    let notes = repo.release_notes(&tags[0], &tags[1]).unwrap();
+    assert_eq!(notes.get_entries().len(), 5);
+    assert_eq!(notes.group(Kind::Breaking).len(), 2);
+    assert_eq!(notes.group(Kind::Feature)[0].get_scope().unwrap(), "api");
+    assert_eq!(
+        notes.group(Kind::Feature)[0].get_references(),
+        &vec![Reference::PullRequest(45)]
+    );
+    assert_eq!(
+        notes.group(Kind::Fix)[0].get_references(),
+        &vec![Reference::Issue(12)]
+    );
+    assert_eq!(
+        notes.group(Kind::Other)[0].get_references(),
+        &vec![Reference::Issue(7)]
+    );
+    let md = notes.render(Some("https://example.com/o/r/"));
+    assert!(md.starts_with(
+        "## v2.0.0\n\nv1.0.0...v2.0.0\n\n### Thay đổi không tương thích\n\n- drop v1 endpoints ("
+    ));
+    assert!(md.contains("- **api:** add search ([#45](https://example.com/o/r/pull/45)) ("));
+    assert!(md.contains("[#12](https://example.com/o/r/issues/12)"));
+    assert!(md.contains("  config moved to toml\n"));
+    assert!(notes.render(None).contains("crash on empty input (#12) ("));
+    let light = Tag::lightweight(
+        "t".into(),
+        a.get_tree_id(),
+        gitlike::repository::git::Kind::Tree,
+    );
+    assert!(repo.release_notes(&tags[0], &light).is_none());
+}
+
+#[test]
+fn merges_are_left_out_and_empty_ranges_render_no_sections() {
+    let repo = Repository::new("mem".into());
+    let a = mk(&[], 1, "chore: init", &[("f", "1"), ("g", "1")]);
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    repo.add_branch(Branch::new("side".into(), a.clone(), 1));
+    repo.checkout("side", "alice", 2);
+    repo.commit(mk(&[], 3, "fix: side fix", &[("f", "1"), ("g", "2")]))
+        .unwrap();
+    repo.checkout("main", "alice", 4);
+    repo.commit(mk(&[], 5, "feat: main feature", &[("f", "2"), ("g", "1")]))
+        .unwrap();
+    let merged = repo.merge("side", "alice", 6).unwrap();
+    repo.add_tag(Tag::new("v1.0.0".into(), &a, "one".into()), "alice", 7);
+    repo.add_tag(Tag::new("v1.1.0".into(), &merged, "two".into()), "alice", 7);
+    let tags = repo.get_tags().lock().unwrap().clone();
+    let notes = repo.release_notes(&tags[0], &tags[1]).unwrap();
+    assert_eq!(notes.get_entries().len(), 2);
+    assert_eq!(notes.group(Kind::Other).len(), 0);
+    let same = repo.release_notes(&tags[1], &tags[1]).unwrap();
+    assert!(same.get_entries().is_empty());
+    assert!(!same.render(None).contains("###"));
+}
+
+#[test]
+fn entries_shorten_hashes_by_characters() {
+    let commit = Commit::new("ħąšħ-ħąšħ".into(), "alice".into(), "fix: crash".into(), 1);
+    let notes = Notes::new("a".into(), "b".into(), vec![Entry::from_commit(&commit)]);
+    assert!(notes.render(None).contains("- crash (ħąšħ-ħą)\n"));
+}
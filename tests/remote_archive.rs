@@ -0,0 +1,33 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+
+#[test]
+fn remote_archive() {
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let mut r = Remote::new("origin".into(), "/x".into());
+    for (n, t) in [("main", 1), ("a", 5), ("b", 2), ("release/1", 0), ("c", 9)] {
+        r.add_branch(Branch::new(n.into(), a.clone(), t));
+    }
+    r.set_default(Some("main".into()));
+    let rep = r.archive_with(&remote::Policy::new(2, vec!["release/*".into()], true));
+    assert_eq!(
+        rep.get_archived(),
+        &vec![
+            ("b".to_string(), 2),
+            ("a".to_string(), 5),
+            ("c".to_string(), 9)
+        ]
+    );
+    assert_eq!(r.get_branches().lock().unwrap().len(), 5);
+    let rep = r.archive_old_branches(3);
+    assert_eq!(
+        rep.get_archived(),
+        &vec![("release/1".to_string(), 0), ("b".to_string(), 2)]
+    );
+    assert_eq!(r.get_branches().lock().unwrap().len(), 3);
+    assert_eq!(r.get_archived().lock().unwrap().len(), 2);
+    assert!(r.get_archived().lock().unwrap()[0].is_archived());
+    assert!(branch::matches("feat/*/x", "feat/a/b/x"));
+    assert!(!branch::matches("feat/*", "fix/a"));
+}
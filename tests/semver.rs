@@ -0,0 +1,109 @@
+mod common;
+use common::*;
+use gitlike::repository::semver::{Identifier, Version};
+use gitlike::repository::*;
+
+#[test]
+fn parse_and_order() {
+    let v = Version::parse("v1.2.3-rc.1+build.5").unwrap();
+    assert_eq!((v.get_major(), v.get_minor(), v.get_patch()), (1, 2, 3));
+    assert_eq!(
+        v.get_pre(),
+        &vec![Identifier::Text("rc".into()), Identifier::Numeric(1)]
+    );
+    assert_eq!(v.to_string(), "1.2.3-rc.1+build.5");
+    for bad in [
+        "1.2", "1.2.3.4", "01.2.3", "1.2.3-", "1.2.3-01", "x1.2.3", "1.2.3+",
+    ] {
+        assert!(Version::parse(bad).is_none(), "{}", bad);
+    }
+    let order = [
+        "1.0.0-alpha",
+        "1.0.0-alpha.1",
+        "1.0.0-alpha.beta",
+        "1.0.0-beta",
+        "1.0.0-beta.2",
+        "1.0.0-beta.11",
+        "1.0.0-rc.1",
+        "1.0.0",
+        "1.0.1",
+        "1.10.0",
+        "2.0.0",
+    ];
+    let parsed: Vec<Version> = order.iter().map(|t| Version::parse(t).unwrap()).collect();
+    assert!(parsed.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(Version::parse("1.0.0+a"), Version::parse("v1.0.0+b"));
+}
+
+#[test]
+fn queries() {
+    let repo = Repository::new("mem".into());
+    let a = mk(&[], 1, "a", &[("f", "1")]);
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    let b = repo
+        .commit(mk(&[a.get_hash()], 2, "b", &[("f", "2")]))
+        .unwrap();
+    let c = repo
+        .commit(mk(&[b.get_hash()], 3, "c", &[("f", "3")]))
+        .unwrap();
+    let d = repo
+        .commit(mk(&[c.get_hash()], 4, "d", &[("f", "4")]))
+        .unwrap();
+    assert_eq!(repo.describe(d.get_hash(), true), None);
+    repo.add_tag(Tag::new("v1.10.0".into(), &b, "ten".into()), "alice", 5);
+    repo.add_tag(Tag::new("v1.9.0".into(), &a, "nine".into()), "alice", 5);
+    repo.add_tag(Tag::new("v2.0.0-rc.1".into(), &c, "rc".into()), "alice", 5);
+    repo.add_tag(Tag::new("nightly".into(), &d, "n".into()), "alice", 5);
+    let names = |v: Vec<(Version, Tag)>| {
+        v.into_iter()
+            .map(|(_, t)| t.get_name().clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        names(repo.get_versions(true)),
+        ["v1.9.0", "v1.10.0", "v2.0.0-rc.1"]
+    );
+    assert_eq!(names(repo.get_versions(false)), ["v1.9.0", "v1.10.0"]);
+    assert_eq!(
+        repo.get_latest_version(false).unwrap().get_name(),
+        "v1.10.0"
+    );
+    assert_eq!(
+        repo.get_latest_version(true).unwrap().get_name(),
+        "v2.0.0-rc.1"
+    );
+    assert_eq!(
+        repo.describe(d.get_hash(), true).unwrap(),
+        format!("v2.0.0-rc.1-1-g{}", &d.get_hash()[..7])
+    );
+    assert_eq!(
+        repo.describe(d.get_hash(), false).unwrap(),
+        format!("v1.10.0-2-g{}", &d.get_hash()[..7])
+    );
+    assert_eq!(repo.describe(b.get_hash(), false).unwrap(), "v1.10.0");
+    assert_eq!(repo.describe(a.get_hash(), false).unwrap(), "v1.9.0");
+}
+
+#[test]
+fn huge_and_empty_fields() {
+    assert!(Version::parse("99999999999999999999.0.0").is_none());
+    assert!(Version::parse("").is_none());
+    assert!(Version::parse("v").is_none());
+    assert!(Version::parse("1.0.0-a..b").is_none());
+    assert!(Version::parse("1.0.0-rc.1") < Version::parse("1.0.0-rc.1.0"));
+}
+
+#[test]
+fn describe_shortens_by_characters() {
+    let repo = Repository::new("mem".into());
+    let a = mk(&[], 1, "a", &[("f", "1")]);
+    let mut b = Commit::new("ħąšħ-ħąšħ".into(), "alice".into(), "b".into(), 2);
+    b.add_parent(a.get_hash().clone());
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    repo.add_branch(Branch::new("topic".into(), b, 2));
+    repo.add_tag(Tag::new("v1.0.0".into(), &a, "one".into()), "alice", 3);
+    assert_eq!(
+        repo.describe("ħąšħ-ħąšħ", false).unwrap(),
+        "v1.0.0-1-għąšħ-ħą"
+    );
+}
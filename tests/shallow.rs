@@ -0,0 +1,97 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+
+#[test]
+fn shallow_partial() {
+    let dir = std::env::temp_dir().join(format!("shallow-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let up = dir.join("up").to_string_lossy().to_string();
+    let origin = Repository::new(up.clone());
+    origin.add_branch(Branch::new(
+        "main".into(),
+        mk(&[], 1, "c1", &[("f", "1\n")]),
+        1,
+    ));
+    origin.commit(mk(&[], 10, "c2", &[("f", "2\n")])).unwrap();
+    origin
+        .commit(mk(
+            &[],
+            20,
+            "c3",
+            &[("f", "3\n"), ("big", &"x".repeat(100)), ("docs/a", "doc")],
+        ))
+        .unwrap();
+    let c4 = origin
+        .commit(mk(
+            &[],
+            30,
+            "c4",
+            &[("f", "4\n"), ("big", &"x".repeat(100)), ("docs/a", "doc")],
+        ))
+        .unwrap();
+    origin.save().unwrap();
+
+    let mut o = transport::Options::new();
+    o.set_depth(Some(2));
+    let shallow =
+        transport::clone(&up, dir.join("s").to_string_lossy().to_string(), &o, 40).unwrap();
+    assert_eq!(shallow.get_graph().lock().unwrap().len(), 2);
+    assert!(shallow.is_shallow());
+    assert_eq!(shallow.resolve("main").unwrap(), *c4.get_hash());
+
+    let mut o = transport::Options::new();
+    o.set_since(Some(10));
+    let since = transport::clone(&up, dir.join("t").to_string_lossy().to_string(), &o, 40).unwrap();
+    assert_eq!(since.get_graph().lock().unwrap().len(), 3);
+    transport::fetch(&since, "origin", 41).unwrap();
+    assert_eq!(since.get_graph().lock().unwrap().len(), 3);
+
+    let mut o = transport::Options::new();
+    o.set_filter(Some(store::Filter::new(Some(50), vec!["docs".into()])));
+    let partial =
+        transport::clone(&up, dir.join("p").to_string_lossy().to_string(), &o, 40).unwrap();
+    let missing = partial
+        .get_graph()
+        .lock()
+        .unwrap()
+        .get_commit(c4.get_hash())
+        .unwrap()
+        .get_missing();
+    assert_eq!(missing.len(), 2);
+    assert_eq!(
+        partial.read_file(c4.get_hash(), "big").unwrap(),
+        "x".repeat(100).into_bytes()
+    );
+    assert_eq!(
+        partial.read_file(c4.get_hash(), "docs/a").unwrap(),
+        b"doc".to_vec()
+    );
+    assert_eq!(
+        partial
+            .get_graph()
+            .lock()
+            .unwrap()
+            .get_commit(c4.get_hash())
+            .unwrap()
+            .get_missing()
+            .len(),
+        0
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn filter_specs() {
+    let filter = store::Filter::parse(&["blob:limit=10".into(), "path:docs".into()]).unwrap();
+    assert_eq!(filter.get_limit(), Some(10));
+    assert_eq!(filter.get_paths(), &vec!["docs".to_string()]);
+    assert_eq!(
+        store::Filter::parse(&["blob:none".into()])
+            .unwrap()
+            .get_limit(),
+        Some(0)
+    );
+    assert!(store::Filter::parse(&["blob:limit=big".into()]).is_none());
+    assert!(store::Filter::parse(&["tree:0".into()]).is_none());
+}
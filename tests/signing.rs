@@ -0,0 +1,181 @@
+mod common;
+use common::*;
+use gitlike::repository::signing::{AllowedSigners, Signer, Trust};
+use gitlike::repository::tag::Verification;
+use gitlike::repository::*;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn keygen(dir: &std::path::Path, args: &[&str], input: &[u8]) -> (bool, String) {
+    let mut child = Command::new("ssh-keygen")
+        .current_dir(dir)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    let out = child.wait_with_output().unwrap();
+    (
+        out.status.success(),
+        if out.status.success() {
+            String::from_utf8_lossy(&out.stdout).to_string()
+        } else {
+            String::from_utf8_lossy(&out.stderr).to_string()
+        },
+    )
+}
+
+#[test]
+fn ssh_keygen_interop() {
+    let dir = std::env::temp_dir().join(format!("signing-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    assert!(
+        keygen(
+            &dir,
+            &["-q", "-t", "ed25519", "-N", "", "-C", "me", "-f", "key"],
+            b""
+        )
+        .0
+    );
+    let signer = Signer::from_openssh(&std::fs::read_to_string(dir.join("key")).unwrap()).unwrap();
+    let public = std::fs::read_to_string(dir.join("key.pub")).unwrap();
+    assert!(public.starts_with(&signer.public_key()));
+    std::fs::write(
+        dir.join("allowed"),
+        format!("alice@x namespaces=\"git\" {}", public),
+    )
+    .unwrap();
+    let payload = b"hello payload\n";
+    std::fs::write(dir.join("sig"), signer.sign(payload)).unwrap();
+    let (ok, out) = keygen(
+        &dir,
+        &[
+            "-Y", "verify", "-f", "allowed", "-I", "alice@x", "-n", "git", "-s", "sig",
+        ],
+        payload,
+    );
+    assert!(ok, "{}", out);
+    let (ok, out) = keygen(&dir, &["-Y", "sign", "-f", "key", "-n", "git"], payload);
+    assert!(ok, "{}", out);
+    let theirs = out;
+    let allowed =
+        AllowedSigners::parse(&std::fs::read_to_string(dir.join("allowed")).unwrap()).unwrap();
+    assert_eq!(
+        allowed.verify(payload, &theirs),
+        Trust::Good("alice@x".into())
+    );
+    assert_eq!(allowed.verify(b"other", &theirs), Trust::Bad);
+    assert_eq!(
+        AllowedSigners::new().verify(payload, &theirs),
+        Trust::Untrusted
+    );
+    let file_only =
+        AllowedSigners::parse(&format!("alice@x namespaces=\"file\" {}", public)).unwrap();
+    assert_eq!(file_only.verify(payload, &theirs), Trust::Untrusted);
+    let both = AllowedSigners::parse(&format!(
+        "alice@x valid-after=\"20200101\",namespaces=\"file,git\" {}",
+        public
+    ))
+    .unwrap();
+    assert_eq!(both.verify(payload, &theirs), Trust::Good("alice@x".into()));
+    std::fs::write(
+        dir.join("both"),
+        format!("alice@x namespaces=\"file,git\" {}", public),
+    )
+    .unwrap();
+    let (ok, out) = keygen(
+        &dir,
+        &[
+            "-Y", "verify", "-f", "both", "-I", "alice@x", "-n", "git", "-s", "sig",
+        ],
+        payload,
+    );
+    assert!(ok, "{}", out);
+    let (ok, other) = keygen(&dir, &["-Y", "sign", "-f", "key", "-n", "file"], payload);
+    assert!(ok, "{}", other);
+    assert_eq!(allowed.verify(payload, &other), Trust::Bad);
+    let open = AllowedSigners::parse(&format!("alice@x {}", public)).unwrap();
+    assert_eq!(open.verify(payload, &theirs), Trust::Good("alice@x".into()));
+    assert_eq!(open.verify(payload, &other), Trust::Bad);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn commits_tags_and_log() {
+    let dir = std::env::temp_dir().join(format!("signing-repo-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let alice = Signer::from_seed(&[7; 32]);
+    let mallory = Signer::from_seed(&[9; 32]);
+    let mut allowed = AllowedSigners::new();
+    allowed.add("alice", &alice.public_key()).unwrap();
+    let repo = Repository::new(dir.to_string_lossy().to_string());
+    let mut a = mk(&[], 1, "first\n", &[("f", "1")]);
+    a.sign(|p| alice.sign(p));
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    let mut b = mk(&[a.get_hash()], 2, "second\n", &[("f", "2")]);
+    b.sign(|p| mallory.sign(p));
+    let b = repo.commit(b).unwrap();
+    let c = repo
+        .commit(mk(&[b.get_hash()], 3, "third\n", &[("f", "3")]))
+        .unwrap();
+    assert_eq!(
+        a.verify(|p, s| allowed.verify(p, s) != Trust::Bad),
+        Verification::Good
+    );
+    assert_eq!(
+        repo.verify_commit(a.get_hash(), &allowed),
+        Some(Trust::Good("alice".into()))
+    );
+    assert_eq!(
+        repo.verify_commit(b.get_hash(), &allowed),
+        Some(Trust::Untrusted)
+    );
+    assert_eq!(
+        repo.verify_commit(c.get_hash(), &allowed),
+        Some(Trust::Unsigned)
+    );
+    let mut tag = Tag::new("v1".into(), &c, "release".into());
+    tag.sign(|p| alice.sign(p));
+    repo.add_tag(tag, "alice", 4);
+    assert_eq!(
+        repo.verify_tag("v1", &allowed),
+        Some(Trust::Good("alice".into()))
+    );
+    let log = repo.log(c.get_hash(), &allowed);
+    assert_eq!(
+        log,
+        vec![
+            format!("{} N alice third", &c.get_hash()[..7]),
+            format!("{} U alice second", &b.get_hash()[..7]),
+            format!("{} G alice first", &a.get_hash()[..7]),
+        ]
+    );
+    repo.save().unwrap();
+    let back = Repository::open(dir.to_string_lossy().to_string()).unwrap();
+    assert_eq!(back.log(c.get_hash(), &allowed), log);
+    assert_eq!(
+        back.verify_tag("v1", &allowed),
+        Some(Trust::Good("alice".into()))
+    );
+    let mut forged = a.clone();
+    forged.sign(|p| alice.sign(p).replace("AAAA", "AAAB"));
+    assert_ne!(
+        forged.verify(|p, s| allowed.verify(p, s) == Trust::Good("alice".into())),
+        Verification::Good
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn log_shortens_hashes_by_characters() {
+    let repo = Repository::new("mem".into());
+    let commit = Commit::new("ħąšħ-ħąšħ".into(), "alice".into(), "first".into(), 1);
+    repo.add_branch(Branch::new("main".into(), commit, 1));
+    assert_eq!(
+        repo.log("ħąšħ-ħąšħ", &AllowedSigners::new()),
+        vec!["ħąšħ-ħą N alice first".to_string()]
+    );
+}
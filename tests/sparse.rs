@@ -0,0 +1,105 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+use sparse::Sparse;
+
+#[test]
+fn cone_and_patterns() {
+    let cone = Sparse::cone(vec!["a/b".into()]);
+    assert!(cone.includes("top"));
+    assert!(cone.includes("a/x"));
+    assert!(cone.includes("a/b/c/d"));
+    assert!(!cone.includes("a/c/d"));
+    assert!(!cone.includes("z/y"));
+    let pat = Sparse::patterns(vec![
+        "/*".into(),
+        "!/*/".into(),
+        "/docs/".into(),
+        "*.md".into(),
+        "!docs/secret/".into(),
+    ]);
+    assert!(pat.includes("README"));
+    assert!(!pat.includes("src/lib.rs"));
+    assert!(pat.includes("src/notes.md"));
+    assert!(pat.includes("docs/a/b"));
+    assert!(!pat.includes("docs/secret/x"));
+}
+
+#[test]
+fn worktree() {
+    let dir = std::env::temp_dir().join(format!("sparse-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let wt = dir.join("wt");
+    let repo = Repository::new(dir.join("repo").to_string_lossy().to_string());
+    repo.add_branch(Branch::new(
+        "main".into(),
+        mk(
+            &[],
+            1,
+            "init",
+            &[("top", "t"), ("a/b/f", "1"), ("a/c/g", "2"), ("z/h", "3")],
+        ),
+        1,
+    ));
+    assert_eq!(repo.materialize(&wt).unwrap(), 4);
+    repo.set_sparse(Some(Sparse::cone(vec!["a/b".into()])));
+    repo.materialize(&wt).unwrap();
+    assert!(!wt.join("z").exists());
+    assert!(!wt.join("a/c").exists());
+    assert!(wt.join("a/b/f").exists());
+    assert!(repo.status(&wt).unwrap().is_empty());
+    std::fs::write(wt.join("a/b/f"), "changed").unwrap();
+    std::fs::write(wt.join("new"), "n").unwrap();
+    assert_eq!(
+        repo.status(&wt).unwrap(),
+        vec![
+            ("a/b/f".into(), worktree::State::Modified),
+            ("new".into(), worktree::State::Added)
+        ]
+    );
+    let snap = repo.capture(&wt).unwrap();
+    assert_eq!(snap.len(), 5);
+    assert_eq!(snap["z/h"], b"3".to_vec());
+    let c = repo
+        .commit(Commit::from_snapshot(
+            &snap,
+            vec![],
+            "alice".into(),
+            "m".into(),
+            2,
+        ))
+        .unwrap();
+    assert_eq!(text(&c, "a/c/g"), "2");
+    repo.save().unwrap();
+    assert_eq!(
+        Repository::open(dir.join("repo").to_string_lossy().to_string())
+            .unwrap()
+            .get_sparse(),
+        repo.get_sparse()
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn widening_restores_files() {
+    let dir = std::env::temp_dir().join(format!("sparse-widen-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let wt = dir.join("wt");
+    let repo = Repository::new(dir.join("repo").to_string_lossy().to_string());
+    repo.add_branch(Branch::new(
+        "main".into(),
+        mk(&[], 1, "init", &[("a/f", "1"), ("z/h", "3")]),
+        1,
+    ));
+    repo.set_sparse(Some(Sparse::patterns(vec!["a/".into()])));
+    repo.materialize(&wt).unwrap();
+    assert!(!wt.join("z/h").exists());
+    std::fs::create_dir_all(wt.join("z")).unwrap();
+    std::fs::write(wt.join("z/h"), "stray").unwrap();
+    assert!(repo.status(&wt).unwrap().is_empty());
+    repo.set_sparse(None);
+    repo.materialize(&wt).unwrap();
+    assert_eq!(std::fs::read(wt.join("z/h")).unwrap(), b"3".to_vec());
+    assert!(repo.status(&wt).unwrap().is_empty());
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,117 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+
+#[test]
+fn round_trip() {
+    let dir = std::env::temp_dir().join(format!("ssh-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let origin = Repository::new(dir.join("srv/proj").to_string_lossy().to_string());
+    origin.add_branch(Branch::new("main".into(), a.clone(), 1));
+    let b = origin.commit(mk(&[], 2, "b", &[("f", "2\n")])).unwrap();
+    origin.save().unwrap();
+
+    let local = Repository::new(dir.join("down").to_string_lossy().to_string());
+    local.add_branch(Branch::new("main".into(), a.clone(), 1));
+    let mut remote = Remote::new("origin".into(), "ssh://example.com:2222/proj".into());
+    remote.set_ssh_command(Some(format!(
+        "{} {}",
+        env!("CARGO_BIN_EXE_fake-ssh"),
+        dir.join("srv").display()
+    )));
+    local.add_remote(remote);
+    let r = transport::fetch(&local, "origin", 3).unwrap();
+    assert!(r.is_ok());
+    assert_eq!(r.get_objects(), 3);
+    let ctx = protection::Context::new("bob".into());
+    local
+        .update_ref("main", a.get_hash(), b.get_hash(), &ctx, 4)
+        .unwrap();
+    let c = local.commit(mk(&[], 5, "c", &[("f", "3\n")])).unwrap();
+    let p = transport::push(&local, "origin", &[], false, 6).unwrap();
+    assert!(p.is_ok());
+    let reopened = Repository::open(dir.join("srv/proj").to_string_lossy().to_string()).unwrap();
+    assert_eq!(reopened.resolve("main").unwrap(), *c.get_hash());
+    let mut missing = Remote::new("missing".into(), "ssh://h/none".into());
+    missing.set_ssh_command(Some(format!(
+        "{} {}",
+        env!("CARGO_BIN_EXE_fake-ssh"),
+        dir.join("srv").display()
+    )));
+    local.add_remote(missing);
+    assert!(transport::fetch(&local, "missing", 7).is_err());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn quoted_paths_reach_the_right_repository() {
+    let dir = std::env::temp_dir().join(format!("ssh-quoted-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let origin = Repository::new(dir.join("srv/it's here").to_string_lossy().to_string());
+    origin.add_branch(Branch::new("main".into(), a.clone(), 1));
+    origin.save().unwrap();
+    let canary = dir.join("canary");
+    let local = Repository::new(dir.join("down").to_string_lossy().to_string());
+    for (name, url) in [
+        ("quoted", "ssh://h/it's here".to_string()),
+        (
+            "injected",
+            format!("ssh://h/x'; touch '{}", canary.display()),
+        ),
+    ] {
+        let mut remote = Remote::new(name.into(), url);
+        remote.set_ssh_command(Some(format!(
+            "{} {}",
+            env!("CARGO_BIN_EXE_fake-ssh"),
+            dir.join("srv").display()
+        )));
+        local.add_remote(remote);
+    }
+    let r = transport::fetch(&local, "quoted", 2).unwrap();
+    assert_eq!(r.get_objects(), 3);
+    assert!(transport::fetch(&local, "injected", 3).is_err());
+    assert!(!canary.exists());
+    assert!(ssh::Client::new("ssh://-oProxyCommand=x/proj", None).is_err());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn serves_in_process() {
+    let dir = std::env::temp_dir().join(format!("ssh-serve-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let origin = Repository::new(dir.join("proj").to_string_lossy().to_string());
+    origin.add_branch(Branch::new("main".into(), a.clone(), 1));
+    origin.save().unwrap();
+
+    let mut output = Vec::new();
+    ssh::serve("git-upload-pack 'proj'", &dir, &b"0000"[..], &mut output).unwrap();
+    assert!(String::from_utf8_lossy(&output).contains("refs/heads/main"));
+
+    let b = mk(&[a.get_hash()], 2, "b", &[("f", "2\n")]);
+    let mut pack = pack::Pack::new();
+    pack.add_commit(&b);
+    let command = store::Command::new(
+        a.get_hash().clone(),
+        b.get_hash().clone(),
+        "refs/heads/main".into(),
+    );
+    let request = protocol::receive_request(&[command], &pack);
+    let mut output = Vec::new();
+    ssh::serve("git-receive-pack 'proj'", &dir, &request[..], &mut output).unwrap();
+    let reopened = Repository::open(dir.join("proj").to_string_lossy().to_string()).unwrap();
+    assert_eq!(reopened.resolve("main").unwrap(), *b.get_hash());
+
+    let request =
+        protocol::upload_request(&store::Request::new(vec![b.get_hash().clone()], vec![]));
+    let mut output = Vec::new();
+    ssh::serve("git-upload-pack proj", &dir, &request[..], &mut output).unwrap();
+    assert!(!output.is_empty());
+
+    let mut sink = Vec::new();
+    assert!(ssh::serve("git-upload-pack '../proj'", &dir, &b"0000"[..], &mut sink).is_err());
+    assert!(ssh::serve("rm -rf", &dir, &b"0000"[..], &mut sink).is_err());
+    let _ = std::fs::remove_dir_all(&dir);
+}
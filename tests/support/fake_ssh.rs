@@ -0,0 +1,10 @@
+use gitlike::repository::ssh;
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let root = std::path::PathBuf::from(&args[1]);
+    let request = args.last().unwrap();
+    if let Err(e) = ssh::serve(request, &root, std::io::stdin(), std::io::stdout()) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,157 @@
+mod common;
+use common::*;
+use gitlike::repository::git::Kind;
+use gitlike::repository::tag::{self, Annotation, Verification};
+use gitlike::repository::*;
+use std::process::Command;
+
+fn sig(payload: &[u8]) -> String {
+    format!(
+        "-----BEGIN SSH SIGNATURE-----\n{}\n-----END SSH SIGNATURE-----\n",
+        payload.len()
+    )
+}
+
+fn git(dir: &std::path::Path, args: &[&str]) -> String {
+    let out = Command::new("git")
+        .arg("--git-dir")
+        .arg(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    String::from_utf8(out.stdout).unwrap()
+}
+
+fn setup(path: &str) -> (Repository, Commit) {
+    let repo = Repository::new(path.into());
+    let c1 = mk(&[], 100, "first\n", &[("a", "1"), ("b/c", "2")]);
+    repo.add_branch(Branch::new("main".into(), c1.clone(), 100));
+    let blob = c1.get_blob("b/c").unwrap().lock().unwrap().get_id().clone();
+    repo.add_tag(Tag::new("v1".into(), &c1, "release\n".into()), "alice", 1);
+    repo.add_tag(
+        Tag::lightweight("light".into(), c1.get_hash().clone(), Kind::Commit),
+        "alice",
+        2,
+    );
+    repo.add_tag(
+        Tag::lightweight("tree".into(), c1.get_tree_id(), Kind::Tree),
+        "alice",
+        3,
+    );
+    let mut signed = Tag::annotated(
+        "blob".into(),
+        blob,
+        Kind::Blob,
+        Annotation::new("bob".into(), 50, "the c file".into()),
+    );
+    signed.sign(sig);
+    repo.add_tag(signed, "alice", 4);
+    (repo, c1)
+}
+
+#[test]
+fn kinds_and_signatures() {
+    let (repo, c1) = setup("mem");
+    let tags = repo.get_tags().lock().unwrap().clone();
+    assert!(tags[0].is_annotated());
+    assert_eq!(tags[0].get_commit(), Some(c1.get_hash()));
+    assert_eq!(tags[0].get_annotation().unwrap().get_tagger(), "alice");
+    assert_ne!(tags[0].get_id(), *c1.get_hash());
+    assert!(!tags[1].is_annotated());
+    assert_eq!(tags[1].get_message(), "");
+    assert_eq!(tags[1].get_id(), *c1.get_hash());
+    assert_eq!(tags[2].get_commit(), None);
+    assert_eq!(tags[3].get_message(), "the c file\n");
+    let good = |p: &[u8], s: &str| s == sig(p);
+    assert!(matches!(tags[3].verify(good), Verification::Good));
+    assert!(matches!(
+        tags[3].verify(|_: &[u8], _: &str| false),
+        Verification::Bad
+    ));
+    assert!(matches!(tags[0].verify(good), Verification::Unsigned));
+    let decoded = tag::decode(&tags[3].get_id(), &tags[3].encode().unwrap()).unwrap();
+    assert_eq!(decoded, tags[3]);
+    assert_eq!(
+        decoded
+            .get_annotation()
+            .unwrap()
+            .get_signature()
+            .map(|s| s.as_str()),
+        Some(sig(&tags[3].payload().unwrap()).as_str())
+    );
+}
+
+#[test]
+#[should_panic]
+fn tagging_unknown_commit() {
+    let (repo, _) = setup("mem");
+    repo.add_tag(
+        Tag::lightweight("x".into(), "f".repeat(40), Kind::Commit),
+        "alice",
+        5,
+    );
+}
+
+#[test]
+fn store_round_trip() {
+    let dir = std::env::temp_dir().join(format!("tags-store-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let (repo, _) = setup(dir.to_str().unwrap());
+    repo.save().unwrap();
+    let back = Repository::open(dir.to_string_lossy().to_string()).unwrap();
+    let mut a = repo.get_tags().lock().unwrap().clone();
+    let mut b = back.get_tags().lock().unwrap().clone();
+    a.sort_by(|x, y| x.get_name().cmp(y.get_name()));
+    b.sort_by(|x, y| x.get_name().cmp(y.get_name()));
+    assert_eq!(a, b);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn git_round_trip() {
+    let dir = std::env::temp_dir().join(format!("tags-git-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let (repo, c1) = setup("mem");
+    let ids = repo.save_git(&dir).unwrap();
+    git(&dir, &["fsck", "--strict"]);
+    assert_eq!(git(&dir, &["cat-file", "-t", "v1"]), "tag\n");
+    assert_eq!(git(&dir, &["cat-file", "-t", "light"]), "commit\n");
+    assert_eq!(git(&dir, &["cat-file", "-t", "tree"]), "tree\n");
+    assert_eq!(git(&dir, &["cat-file", "-p", "blob^{}"]), "2");
+    assert_eq!(
+        git(&dir, &["rev-parse", "tree"]),
+        git(&dir, &["rev-parse", "main^{tree}"])
+    );
+    assert!(git(&dir, &["cat-file", "-p", "blob"]).contains("-----BEGIN SSH SIGNATURE-----"));
+    let back = Repository::open_git(&dir).unwrap();
+    let tags = back.get_tags().lock().unwrap().clone();
+    let find = |n: &str| tags.iter().find(|t| t.get_name() == n).unwrap().clone();
+    assert_eq!(find("v1").get_commit(), Some(&ids[c1.get_hash()]));
+    assert_eq!(find("v1").get_message(), "release\n");
+    assert!(!find("light").is_annotated());
+    assert_eq!(find("tree").get_target(), &c1.get_tree_id());
+    assert_eq!(find("blob").get_kind(), Kind::Blob);
+    assert!(find("blob")
+        .get_annotation()
+        .unwrap()
+        .get_signature()
+        .is_some());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn tampered_tags() {
+    let (repo, _) = setup("mem");
+    let tags = repo.get_tags().lock().unwrap().clone();
+    let body = String::from_utf8(tags[3].encode().unwrap()).unwrap();
+    let forged = body.replace("the c file", "the cc file");
+    let decoded = tag::decode(&tags[3].get_id(), forged.as_bytes()).unwrap();
+    let good = |p: &[u8], s: &str| s == sig(p);
+    assert!(matches!(decoded.verify(good), Verification::Bad));
+    assert!(tag::decode(&tags[3].get_id(), b"not a tag").is_err());
+}
@@ -0,0 +1,86 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+
+#[test]
+fn tracking_flow() {
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let b = mk(&[a.get_hash()], 2, "b", &[("f", "2\n")]);
+    let c = mk(&[b.get_hash()], 3, "c", &[("f", "3\n")]);
+    let repo = Repository::new("/tmp/r".into());
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    let remote = Remote::new("origin".into(), "/tmp/o".into());
+    let rb = Branch::new("main".into(), a.clone(), 1);
+    rb.add_commit(b.clone()).unwrap();
+    rb.add_commit(c.clone()).unwrap();
+    remote.add_branch(rb);
+    repo.add_remote(remote);
+    assert!(repo.tracking("main").is_none());
+    repo.set_upstream(
+        "main",
+        Some(tracking::Upstream::new("origin".into(), "main".into())),
+    );
+    let s = repo.tracking("main").unwrap();
+    assert_eq!(
+        (s.get_ahead(), s.get_behind(), s.can_fast_forward()),
+        (0, 2, true)
+    );
+    assert!(!repo.get_graph().lock().unwrap().contains(c.get_hash()));
+    repo.commit(mk(&[a.get_hash()], 4, "local", &[("g", "x\n")]))
+        .unwrap();
+    let s = repo.tracking("main").unwrap();
+    assert_eq!(
+        (s.get_ahead(), s.get_behind(), s.can_fast_forward()),
+        (1, 2, false)
+    );
+}
+
+#[test]
+fn tracking_while_fetching() {
+    let dir = std::env::temp_dir().join(format!("tracking-fetch-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let up = dir.join("up").to_string_lossy().to_string();
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let origin = Repository::new(up.clone());
+    origin.add_branch(Branch::new("main".into(), a.clone(), 1));
+    origin.commit(mk(&[], 2, "b", &[("f", "2\n")])).unwrap();
+    origin.save().unwrap();
+    let repo = std::sync::Arc::new(Repository::new(
+        dir.join("down").to_string_lossy().to_string(),
+    ));
+    repo.add_branch(Branch::new("main".into(), a.clone(), 1));
+    repo.add_remote(Remote::new("origin".into(), up));
+    transport::fetch(&repo, "origin", 3).unwrap();
+    repo.set_upstream(
+        "main",
+        Some(tracking::Upstream::new("origin".into(), "main".into())),
+    );
+    let (done, finished) = std::sync::mpsc::channel();
+    let fetcher = {
+        let repo = std::sync::Arc::clone(&repo);
+        let done = done.clone();
+        std::thread::spawn(move || {
+            for ts in 0..50 {
+                transport::fetch(&repo, "origin", 4 + ts).unwrap();
+            }
+            done.send(()).unwrap();
+        })
+    };
+    let tracker = {
+        let repo = std::sync::Arc::clone(&repo);
+        std::thread::spawn(move || {
+            for _ in 0..500 {
+                assert_eq!(repo.tracking("main").unwrap().get_behind(), 1);
+            }
+            done.send(()).unwrap();
+        })
+    };
+    for _ in 0..2 {
+        finished
+            .recv_timeout(std::time::Duration::from_secs(60))
+            .expect("deadlock");
+    }
+    fetcher.join().unwrap();
+    tracker.join().unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,164 @@
+mod common;
+use common::*;
+use gitlike::repository::*;
+
+#[test]
+fn fetch_push() {
+    let dir = std::env::temp_dir().join(format!("t037-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let up = dir.join("up").to_string_lossy().to_string();
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let origin = Repository::new(up.clone());
+    origin.add_branch(Branch::new("main".into(), a.clone(), 1));
+    let b = origin
+        .commit(mk(&[], 2, "b", &[("f", "2\n"), ("d/g", "x")]))
+        .unwrap();
+    assert!(origin.save().unwrap() > 0);
+
+    let local = Repository::new(dir.join("down").to_string_lossy().to_string());
+    local.add_branch(Branch::new("main".into(), a.clone(), 1));
+    local.add_remote(Remote::new("origin".into(), format!("file://{}", up)));
+    let r = transport::fetch(&local, "origin", 3).unwrap();
+    assert_eq!(r.get_objects(), 4);
+    assert_eq!(
+        r.get_updates()[0].get_reference(),
+        "refs/remotes/origin/main"
+    );
+    assert_eq!(
+        local
+            .get_graph()
+            .lock()
+            .unwrap()
+            .get_commit(b.get_hash())
+            .map(|c| text(c, "d/g")),
+        Some("x".into())
+    );
+    assert_eq!(
+        transport::fetch(&local, "origin", 4)
+            .unwrap()
+            .get_updates()
+            .len(),
+        0
+    );
+
+    local
+        .update_ref(
+            "main",
+            a.get_hash(),
+            b.get_hash(),
+            &protection::Context::new("bob".into()),
+            5,
+        )
+        .unwrap();
+    let c = local.commit(mk(&[], 6, "c", &[("f", "3\n")])).unwrap();
+    let p = transport::push(&local, "origin", &["main"], false, 7).unwrap();
+    assert!(!p.get_updates()[0].is_forced());
+    let reopened = Repository::open(up.clone()).unwrap();
+    assert_eq!(reopened.resolve("main").unwrap(), *c.get_hash());
+    assert_eq!(reopened.get_graph().lock().unwrap().len(), 3);
+
+    let other = Repository::new(dir.join("other").to_string_lossy().to_string());
+    other.add_branch(Branch::new(
+        "main".into(),
+        mk(&[], 1, "x", &[("f", "9\n")]),
+        1,
+    ));
+    other.add_remote(Remote::new("origin".into(), up.clone()));
+    assert!(!transport::push(&other, "origin", &["main"], false, 8)
+        .unwrap()
+        .is_ok());
+    let refused = transport::push(&other, "origin", &["main"], true, 8).unwrap();
+    assert!(matches!(
+        &refused.get_rejected()[0].1,
+        branch::Rejection::Remote(_, why) if why == "non-fast-forward"
+    ));
+    let served = Repository::open(up.clone()).unwrap();
+    let mut open = protection::Rule::new("main".into());
+    open.set_rewrite(true);
+    open.add_pusher("carol".into());
+    served.protect(open);
+    served.save().unwrap();
+    assert!(!transport::push(&other, "origin", &["main"], true, 9)
+        .unwrap()
+        .is_ok());
+    let mut options = transport::Options::new();
+    options.set_actor(Some("carol".into()));
+    assert!(
+        transport::push_with(&other, "origin", &["main"], true, &options, 9)
+            .unwrap()
+            .get_updates()[0]
+            .is_forced()
+    );
+    let reopened = Repository::open(up.clone()).unwrap();
+    assert_eq!(reopened.get_rules().lock().unwrap().len(), 1);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn save_repairs_damaged_objects() {
+    let dir = std::env::temp_dir().join(format!("store-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = dir.to_string_lossy().to_string();
+    let repo = Repository::new(path.clone());
+    repo.add_branch(Branch::new(
+        "main".into(),
+        mk(&[], 1, "init", &[("f", "hello\n")]),
+        1,
+    ));
+    assert!(repo.save().unwrap() > 0);
+    assert_eq!(repo.save().unwrap(), 0);
+    let id = object::hash("blob", b"hello\n");
+    let file = dir.join("objects").join(&id[..2]).join(&id);
+    std::fs::write(&file, b"blob 6\0hel").unwrap();
+    assert_eq!(repo.save().unwrap(), 1);
+    let reopened = Repository::open(path).unwrap();
+    let head = reopened.resolve("main").unwrap();
+    let graph = reopened.get_graph();
+    let graph = graph.lock().unwrap();
+    assert_eq!(text(graph.get_commit(&head).unwrap(), "f"), "hello\n");
+    let leftovers = std::fs::read_dir(file.parent().unwrap())
+        .unwrap()
+        .filter(|e| e.as_ref().unwrap().path().extension().is_some())
+        .count();
+    assert_eq!(leftovers, 0);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn fetch_rejects_forged_commits() {
+    let dir = std::env::temp_dir().join(format!("forged-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let up = dir.join("up").to_string_lossy().to_string();
+    let a = mk(&[], 1, "init", &[("f", "1\n")]);
+    let origin = Repository::new(up.clone());
+    origin.add_branch(Branch::new("main".into(), a.clone(), 1));
+    origin.save().unwrap();
+    let store = store::Store::new(std::path::Path::new(&up));
+    let body = format!(
+        "tree {}\nauthor mallory\ntimestamp 1\n\ninit",
+        a.get_tree_id()
+    );
+    let path = dir
+        .join("up/objects")
+        .join(&a.get_hash()[..2])
+        .join(a.get_hash());
+    std::fs::write(&path, format!("commit {}\0{}", body.len(), body)).unwrap();
+    assert_eq!(
+        store.read_commit(a.get_hash()).unwrap().get_author(),
+        "mallory"
+    );
+
+    let local = Repository::new(dir.join("down").to_string_lossy().to_string());
+    local.add_remote(Remote::new("origin".into(), up));
+    assert!(matches!(
+        transport::fetch(&local, "origin", 2),
+        Err(transport::Error::Io(_))
+    ));
+    assert!(local
+        .get_graph()
+        .lock()
+        .unwrap()
+        .get_commit(a.get_hash())
+        .is_none());
+    let _ = std::fs::remove_dir_all(&dir);
+}